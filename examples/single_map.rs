@@ -29,7 +29,11 @@ impl Default for MyApp {
     fn default() -> Self {
         let settings = MultiBitmapWidgetSettings {
             start_size: None,
+            keep_aspect_ratio: false,
+            letterbox_color: None,
             id: "test".to_owned(),
+            texture_options: egui::TextureOptions::default(),
+            placeholder_color: Color::DARK_GRAY,
             boundary_between_data: ColorWithThickness {
                 color: Color::DARK_GRAY,
                 thickness: 10,
@@ -47,12 +51,54 @@ impl Default for MyApp {
                 (-3.1235, 12.456),
             )),
             background: Color::BLACK,
+            missing_data_color: None,
             boundary_unselected: ColorWithThickness {
                 color: Color::GRAY,
                 thickness: 7,
             },
             boundary_selected: Color::WHITE,
             boundary_factor_min: 3,
+            downsampling: egui_heatmap::Downsampling::Nearest,
+            scale_bar: None,
+            grid: None,
+            truncate_titles: true,
+            colorbar_labels: egui_heatmap::ColorbarLabels::Auto,
+            colorbar_relative_thickness: None,
+            colorbar_gap: None,
+            drag_dim_factor: 0.5,
+            drag_outline: Some(ColorWithThickness {
+                color: Color::WHITE,
+                thickness: 2,
+            }),
+            selection_style: egui_heatmap::SelectionStyle::Outline,
+            colorbar_font: None,
+            min_font_height: 8.,
+            text_compositing: egui_heatmap::TextCompositing::Additive,
+            legend: None,
+            colorbar_ticks: 5,
+            colorbar_placement: egui_heatmap::ColorbarPlacement::Right,
+            colorbar_formatter: None,
+            min_shown_extent: None,
+            max_shown_extent: None,
+            pan_bounds: None,
+            upsampling: egui_heatmap::Upsampling::Nearest,
+            colorbar_scale: egui_heatmap::ScaleMode::Linear,
+            navigation: egui_heatmap::NavigationConfig::default(),
+            double_click_recenters: true,
+            recenter_snaps_to_data: true,
+            animation_duration: None,
+            horizontal_scroll_factor: 5.,
+            scroll_zoom_sensitivity: 50.,
+            shift_scroll_pans: false,
+            max_selected: None,
+            clip_selection_to_view: false,
+            colorbar_marker: Some(Color::WHITE),
+            extrema_markers: None,
+            clipboard_in_background: true,
+            copy_shortcut: Some(egui::KeyboardShortcut::new(
+                egui::Modifiers::COMMAND,
+                egui::Key::C,
+            )),
         };
         let bitmap = BitmapWidget::with_settings(
             egui_heatmap::Data::<Color>::example(
@@ -78,12 +124,16 @@ impl eframe::App for MyApp {
                 |ui| {
                     ui.vertical(|ui| {
                         ui.checkbox(&mut self.scroll_area, "ScrollArea");
+                        let mut colorbar = self.bitmap.colorbar_visible();
+                        if ui.checkbox(&mut colorbar, "Colorbar").changed() {
+                            self.bitmap.set_colorbar_visible(colorbar);
+                        }
                         if self.scroll_area {
                             egui::scroll_area::ScrollArea::new([true, true]).show(ui, |ui| {
                                 egui::Grid::new("grid").num_columns(2).show(ui, |ui| {
                                     for i in 0..125 {
                                         ui.label("ae Row:");
-                                        ui.label(&i.to_string());
+                                        ui.label(i.to_string());
                                         ui.end_row()
                                     }
                                 });
@@ -92,7 +142,7 @@ impl eframe::App for MyApp {
                             egui::Grid::new("grid").num_columns(2).show(ui, |ui| {
                                 for i in 0..125 {
                                     ui.label("ae Row:");
-                                    ui.label(&i.to_string());
+                                    ui.label(i.to_string());
                                     ui.end_row()
                                 }
                             });
@@ -124,7 +174,7 @@ impl eframe::App for MyApp {
                                 "Selected: ".to_owned()
                                     + &self
                                         .state
-                                        .selected()
+                                        .selected_sorted()
                                         .iter()
                                         .map(|egui_heatmap::CoordinatePoint { x, y }| {
                                             format!("({x}|{y})")
@@ -0,0 +1,48 @@
+// Compares uploading the whole widget as one texture (as done by the widget)
+// with uploading one texture per subplot.
+// This measures the cost within egui only. Each texture delta additionally causes
+// a separate upload in the rendering backend, so the real difference is larger.
+// Run with: cargo run --release --example upload_benchmark
+use eframe::egui;
+
+const SUBPLOTS_PER_AXIS: usize = 8;
+const SUBPLOT_SIZE: usize = 64;
+const FRAMES: usize = 200;
+
+fn main() {
+    let full_size = SUBPLOTS_PER_AXIS * SUBPLOT_SIZE;
+    let single = measure(1, full_size);
+    let per_subplot = measure(SUBPLOTS_PER_AXIS * SUBPLOTS_PER_AXIS, SUBPLOT_SIZE);
+    println!(
+        "{} subplots of {SUBPLOT_SIZE}x{SUBPLOT_SIZE} pixels, {FRAMES} frames",
+        SUBPLOTS_PER_AXIS * SUBPLOTS_PER_AXIS
+    );
+    println!("single texture:      {single:?} per frame");
+    println!("texture per subplot: {per_subplot:?} per frame");
+}
+
+/// Average duration of a frame which updates 'count' textures of the given size
+fn measure(count: usize, size: usize) -> std::time::Duration {
+    let ctx = egui::Context::default();
+    let image = |frame: usize| {
+        egui::ColorImage::new(
+            [size, size],
+            egui_heatmap::colors::get_distinguishable_color(frame),
+        )
+    };
+    ctx.begin_frame(egui::RawInput::default());
+    let mut textures = (0..count)
+        .map(|i| ctx.load_texture(format!("texture {i}"), image(0), Default::default()))
+        .collect::<Vec<_>>();
+    let _ = ctx.end_frame();
+    let start = std::time::Instant::now();
+    for frame in 1..=FRAMES {
+        ctx.begin_frame(egui::RawInput::default());
+        for texture in &mut textures {
+            texture.set(image(frame), Default::default());
+        }
+        let output = ctx.end_frame();
+        assert_eq!(output.textures_delta.set.len(), count);
+    }
+    start.elapsed() / FRAMES as u32
+}
@@ -62,43 +62,192 @@ impl HeatmapData {
         }
         Some(self.pixels[(x + y * self.width) as usize])
     }
-    /// Convert this to a bitmap, using a range
+    /// Locations of the smallest and the largest finite value (the first one in case of ties).
+    /// Returns None if there are no finite values
+    pub fn extrema(&self) -> Option<(BitMapPoint, BitMapPoint)> {
+        let mut finite = self
+            .pixels
+            .iter()
+            .enumerate()
+            .filter(|(_, x)| x.is_finite());
+        let first = finite.next()?;
+        let (min, max) = finite.fold((first, first), |(min, max), x| {
+            (
+                if x.1 < min.1 { x } else { min },
+                if x.1 > max.1 { x } else { max },
+            )
+        });
+        let point = |i: usize| BitMapPoint {
+            x: i as i32 % self.width,
+            y: i as i32 / self.width,
+        };
+        Some((point(min.0), point(max.0)))
+    }
+    /// Compute limits for an automatic contrast stretch ("auto levels"), to be used with 'to_bitmap'.
+    /// In contrast to the plain minimum/maximum, 'clip_pct' percent of the finite values are clipped at each tail.
+    /// So a few outliers do not compress the colors of all other values.
+    /// Non-finite values are ignored. If there are no finite values, (0., 1.) is returned
+    pub fn contrast_stretch(&self, clip_pct: f32) -> (f32, f32) {
+        let mut values = self
+            .pixels
+            .iter()
+            .cloned()
+            .filter(|x| x.is_finite())
+            .collect::<Vec<_>>();
+        if values.is_empty() {
+            return (0., 1.);
+        }
+        values.sort_by(|a, b| a.total_cmp(b));
+        let clip = if clip_pct.is_finite() {
+            clip_pct.clamp(0., 50.) / 100.
+        } else {
+            0.
+        };
+        let last = (values.len() - 1) as f32;
+        let lower = values[(clip * last).round() as usize];
+        let upper = values[((1. - clip) * last).round() as usize];
+        (lower, upper)
+    }
+    /// Histogram equalization: each finite value is replaced by its relative rank in [0.0, 1.0].
+    /// So the colors are spread according to the distribution of the data, which maximizes contrast for clustered values.
+    /// Use the result together with 'to_bitmap' and limits (0., 1.). Non-finite values are kept as no-data
+    pub fn equalize(&self) -> HeatmapData {
+        let mut sorted = self
+            .pixels
+            .iter()
+            .cloned()
+            .filter(|x| x.is_finite())
+            .collect::<Vec<_>>();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let last = sorted.len().saturating_sub(1) as f32;
+        let pixels = self
+            .pixels
+            .iter()
+            .map(|&x| {
+                if !x.is_finite() {
+                    x
+                } else if last == 0. {
+                    0.5
+                } else {
+                    // equal values share their mean rank
+                    let below = sorted.partition_point(|&v| v < x);
+                    let below_or_equal = sorted.partition_point(|&v| v <= x);
+                    (below + below_or_equal - 1) as f32 / 2. / last
+                }
+            })
+            .collect();
+        HeatmapData {
+            width: self.width,
+            height: self.height,
+            pixels,
+        }
+    }
+    /// Convert this to a bitmap, using a range.
+    /// Non-finite values get the color 'missing_data', which may differ from the background of the widget
     pub fn to_bitmap(
         &self,
         limits: (f32, f32),
         options: crate::colors::ColorGradientOptions,
-        background: egui::Color32,
+        missing_data: egui::Color32,
     ) -> BitmapData {
+        self.to_bitmap_with_mask(limits, options, missing_data).0
+    }
+    /// Convert this to a bitmap, using a range.
+    /// Additionally, returns which data points were clamped to the range
+    pub fn to_bitmap_with_mask(
+        &self,
+        limits: (f32, f32),
+        options: crate::colors::ColorGradientOptions,
+        missing_data: egui::Color32,
+    ) -> (BitmapData, ClampMask) {
         let gradient = crate::colors::Gradient::<egui::Color32>::with_options(&options);
+        self.to_bitmap_with_gradient(limits, &gradient, missing_data)
+    }
+    /// Convert this to a bitmap of any color type (e.g. 'char' for debugging), using a range.
+    /// Additionally, returns which data points were clamped to the range
+    pub fn to_bitmap_with_gradient<C: Clone>(
+        &self,
+        limits: (f32, f32),
+        gradient: &crate::colors::Gradient<C>,
+        missing_data: C,
+    ) -> (BitmapData<C>, ClampMask) {
+        self.to_bitmap_scaled_unchecked(limits, gradient, missing_data, ScaleMode::Linear)
+    }
+    /// Convert this to a bitmap, using a range and a (e.g. logarithmic) scale.
+    /// The limits are given in data units, like the colorbar limits of the widget (see 'colorbar_scale').
+    /// Returns None if the scale or the limits are invalid (see 'ScaleMode::is_valid_limits') or the gradient has no colors (see 'Gradient::try_with_options')
+    pub fn to_bitmap_scaled(
+        &self,
+        limits: (f32, f32),
+        options: crate::colors::ColorGradientOptions,
+        missing_data: egui::Color32,
+        scale: ScaleMode,
+    ) -> Option<BitmapData> {
+        let gradient = crate::colors::Gradient::<egui::Color32>::try_with_options(&options)?;
+        self.to_bitmap_scaled_with_gradient(limits, &gradient, missing_data, scale)
+            .map(|(bitmap, _)| bitmap)
+    }
+    /// Convert this to a bitmap of any color type, using a range and a (e.g. logarithmic) scale.
+    /// Values without a scaled value (e.g. non-positive ones for 'Log10') are treated as no-data.
+    /// Additionally, returns which data points were clamped to the range.
+    /// Returns None if the scale or the limits are invalid, see 'ScaleMode::is_valid_limits'
+    pub fn to_bitmap_scaled_with_gradient<C: Clone>(
+        &self,
+        limits: (f32, f32),
+        gradient: &crate::colors::Gradient<C>,
+        missing_data: C,
+        scale: ScaleMode,
+    ) -> Option<(BitmapData<C>, ClampMask)> {
+        scale
+            .is_valid_limits(limits)
+            .then(|| self.to_bitmap_scaled_unchecked(limits, gradient, missing_data, scale))
+    }
+    /// Like 'to_bitmap_scaled_with_gradient', but without checking the scale and the limits
+    fn to_bitmap_scaled_unchecked<C: Clone>(
+        &self,
+        limits: (f32, f32),
+        gradient: &crate::colors::Gradient<C>,
+        missing_data: C,
+        scale: ScaleMode,
+    ) -> (BitmapData<C>, ClampMask) {
         let HeatmapData {
             width,
             height,
             pixels,
         } = self;
+        let limits = (scale.apply(limits.0), scale.apply(limits.1));
         let delta = limits.1 - limits.0;
-        let pixels = pixels
+        let (pixels, mask) = pixels
             .iter()
             .map(|&x| {
+                let x = scale.apply(x);
                 if x.is_finite() {
-                    let x = if x < limits.0 {
-                        limits.0
+                    let (x, clamped) = if x < limits.0 {
+                        (limits.0, Clamped::Below)
                     } else if x > limits.1 {
-                        limits.1
+                        (limits.1, Clamped::Above)
                     } else {
-                        x
+                        (x, Clamped::InRange)
                     };
                     let x = (x - limits.0) / delta;
-                    gradient.lookup_color(x)
+                    (gradient.lookup_color(x), clamped)
                 } else {
-                    background
+                    (missing_data.clone(), Clamped::NoData)
                 }
             })
-            .collect();
-        BitmapData {
-            width: *width,
-            height: *height,
-            pixels,
-        }
+            .unzip();
+        (
+            BitmapData {
+                width: *width,
+                height: *height,
+                pixels,
+            },
+            ClampMask {
+                width: *width,
+                height: *height,
+                pixels: mask,
+            },
+        )
     }
     /// Some demo data set
     pub fn example_circle(width: usize, height: usize) -> Self {
@@ -110,12 +259,7 @@ impl HeatmapData {
                 let distance = [center[0] as f32 - w as f32, center[1] as f32 - h as f32];
                 let distance = distance[0] * distance[0] + distance[1] * distance[1];
                 let distance = distance.sqrt() / max_distance;
-                let mut distance = 1. - distance;
-                if distance < 0. {
-                    distance = 0.;
-                } else if distance > 1. {
-                    distance = 1.;
-                }
+                let distance = (1. - distance).clamp(0., 1.);
                 data.push(distance);
             }
         }
@@ -127,32 +271,148 @@ impl HeatmapData {
     }
 }
 
-/// This represents the data which shall be shown in the widget
-pub struct BitmapData {
+/// Scale which is applied to the values of 'HeatmapData' before the gradient lookup
+#[derive(Debug, Clone, Copy, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+pub enum ScaleMode {
+    /// The values are used as they are
+    #[default]
+    Linear,
+    /// Decadic logarithm, non-positive values are treated as no-data
+    Log10,
+    /// Symmetric logarithm: linear close to zero, logarithmic (keeping the sign) further away.
+    /// 'linthresh' is the (positive) width of the roughly linear region around zero
+    SymLog {
+        /// Width of the roughly linear region around zero
+        linthresh: f32,
+    },
+}
+impl ScaleMode {
+    /// Check the parameters, i.e. that 'linthresh' of 'SymLog' is positive and finite
+    pub fn is_valid(&self) -> bool {
+        match self {
+            ScaleMode::Linear | ScaleMode::Log10 => true,
+            ScaleMode::SymLog { linthresh } => linthresh.is_finite() && *linthresh > 0.,
+        }
+    }
+    /// Check the parameters and the limits, i.e. that both limits can be scaled (e.g. are positive for 'Log10') and the scaled lower limit is below the upper one
+    pub fn is_valid_limits(&self, limits: (f32, f32)) -> bool {
+        let (lower, upper) = (self.apply(limits.0), self.apply(limits.1));
+        self.is_valid() && lower.is_finite() && upper.is_finite() && lower < upper
+    }
+    /// Scale a value. Returns nan if the value cannot be scaled
+    pub fn apply(&self, x: f32) -> f32 {
+        self.apply_f64(x as f64) as f32
+    }
+    /// Inverse of 'apply', e.g. to convert a scaled value back to data units
+    pub fn invert(&self, x: f32) -> f32 {
+        self.invert_f64(x as f64) as f32
+    }
+    pub(crate) fn apply_f64(&self, x: f64) -> f64 {
+        match self {
+            ScaleMode::Linear => x,
+            ScaleMode::Log10 => {
+                if x > 0. {
+                    x.log10()
+                } else {
+                    f64::NAN
+                }
+            }
+            ScaleMode::SymLog { linthresh } => x.signum() * (x.abs() / *linthresh as f64).ln_1p(),
+        }
+    }
+    pub(crate) fn invert_f64(&self, x: f64) -> f64 {
+        match self {
+            ScaleMode::Linear => x,
+            ScaleMode::Log10 => 10f64.powf(x),
+            ScaleMode::SymLog { linthresh } => x.signum() * *linthresh as f64 * x.abs().exp_m1(),
+        }
+    }
+}
+
+/// Indicates whether a data point was clamped to the limits when converting to a bitmap
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Clamped {
+    /// The value is within the limits
+    InRange,
+    /// The value was smaller than the lower limit
+    Below,
+    /// The value was larger than the upper limit
+    Above,
+    /// There is no data (non-finite value)
+    NoData,
+}
+
+/// Mask of clamped data points, see 'HeatmapData::to_bitmap_with_mask'
+pub struct ClampMask {
+    /// Width of the data set
+    pub width: i32,
+    /// Height of the data set
+    pub height: i32,
+    /// Clamping of each data point, row by row
+    pub pixels: Vec<Clamped>,
+}
+impl ClampMask {
+    /// Get the clamping of a point, if data is available
+    pub fn get_at_point(&self, BitMapPoint { x, y }: BitMapPoint) -> Option<Clamped> {
+        if x < 0 || y < 0 {
+            return None;
+        }
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        Some(self.pixels[(x + y * self.width) as usize])
+    }
+    /// Generate overlay text which marks all clamped points, e.g. to be used with 'Overlay::new'.
+    /// 'first_point_coordinate' is the user-given coordinate of the first data point (row 0, column 0)
+    pub fn overlay_text(
+        &self,
+        first_point_coordinate: &crate::CoordinatePoint,
+        below: &str,
+        above: &str,
+    ) -> std::collections::HashMap<crate::CoordinatePoint, String> {
+        self.pixels
+            .iter()
+            .enumerate()
+            .filter_map(|(i, clamped)| {
+                let text = match clamped {
+                    Clamped::Below => below,
+                    Clamped::Above => above,
+                    Clamped::InRange | Clamped::NoData => return None,
+                };
+                let i = i as i32;
+                let point = crate::CoordinatePoint {
+                    x: first_point_coordinate.x + i % self.width,
+                    y: first_point_coordinate.y + i / self.width,
+                };
+                Some((point, text.to_string()))
+            })
+            .collect()
+    }
+}
+
+/// This represents the data which shall be shown in the widget.
+/// By default, the colors are 'egui::Color32'
+pub struct BitmapData<C = egui::Color32> {
     /// Width of the data set
     pub width: i32,
     /// Height of the data set
     pub height: i32,
     /// Colors of the data points, row by row.
-    pub pixels: Vec<egui::Color32>,
+    pub pixels: Vec<C>,
 }
-impl BitmapData {
+impl<C: Clone> BitmapData<C> {
     /// Get the color of a point, if data is available
-    pub fn get_color_at_point(&self, BitMapPoint { x, y }: BitMapPoint) -> Option<egui::Color32> {
+    pub fn get_color_at_point(&self, BitMapPoint { x, y }: BitMapPoint) -> Option<C> {
         if x < 0 || y < 0 {
             return None;
         }
         if x >= self.width || y >= self.height {
             return None;
         }
-        Some(self.pixels[(x + y * self.width) as usize])
+        Some(self.pixels[(x + y * self.width) as usize].clone())
     }
     /// Set the color of a point. Returns previous color, if any. Size of bitmap rectangular is not increased.
-    pub fn set_color_at_point(
-        &mut self,
-        BitMapPoint { x, y }: BitMapPoint,
-        c: egui::Color32,
-    ) -> Option<egui::Color32> {
+    pub fn set_color_at_point(&mut self, BitMapPoint { x, y }: BitMapPoint, c: C) -> Option<C> {
         if x < 0 || y < 0 {
             return None;
         }
@@ -160,8 +420,277 @@ impl BitmapData {
             return None;
         }
         let i = (x + y * self.width) as usize;
-        let old = self.pixels[i];
-        self.pixels[i] = c;
-        Some(old)
+        Some(std::mem::replace(&mut self.pixels[i], c))
     }
+    /// Convert this to data shown by the widgets, with an overlay without texts and title.
+    /// The first point (column 0, row 0) is located at 'first_point'
+    /// ```
+    /// use egui_heatmap::{colors::ColorGradientOptions, Color, CoordinatePoint, Font, FontOptions, HeatmapData};
+    /// let bitmap = HeatmapData::example_circle(20, 10).to_bitmap(
+    ///     (0., 1.),
+    ///     ColorGradientOptions::StartEnd {
+    ///         start: Color::BLUE,
+    ///         end: Color::RED,
+    ///         steps: 32,
+    ///     },
+    ///     Color::BLACK,
+    /// );
+    /// let font = FontOptions {
+    ///     font: Font::EguiMonospace,
+    ///     background_is_transparent: true,
+    ///     background_opacity: 0.,
+    ///     font_height: 18.,
+    ///     rtl: false,
+    ///     horizontal_scale: 1.,
+    /// };
+    /// let data = bitmap.into_data(CoordinatePoint { x: 5, y: -3 }, font, true);
+    /// assert_eq!([data.width, data.height], [20, 10]);
+    /// ```
+    pub fn into_data(
+        self,
+        first_point: crate::CoordinatePoint,
+        font: crate::FontOptions,
+        show_coordinates: bool,
+    ) -> crate::Data<C> {
+        let Self {
+            width,
+            height,
+            pixels,
+        } = self;
+        crate::Data {
+            width: width.max(0) as usize,
+            height: height.max(0) as usize,
+            data: pixels,
+            first_point_coordinate: first_point,
+            overlay: crate::Overlay::builder(font)
+                .show_coordinates(show_coordinates)
+                .build_lossy(),
+        }
+    }
+}
+impl BitmapData {
+    /// Encode the colors as PNG, e.g. for exporting without showing the widget.
+    /// As on screen, the alpha channel is ignored, i.e. the image is opaque
+    pub fn to_png_bytes(&self) -> Result<Vec<u8>, image::ImageError> {
+        encode_png(
+            &self.pixels,
+            self.width.max(0) as usize,
+            self.height.max(0) as usize,
+        )
+    }
+    /// Save the colors as PNG file, see 'to_png_bytes'
+    pub fn save_png<P: AsRef<std::path::Path>>(&self, path: P) -> Result<(), image::ImageError> {
+        std::fs::write(path, self.to_png_bytes()?)?;
+        Ok(())
+    }
+}
+
+/// Encode the given colors (row by row) as opaque PNG, ignoring the alpha channel.
+/// Returns an error if there are fewer colors than 'width' times 'height'
+pub(crate) fn encode_png(
+    pixels: &[egui::Color32],
+    width: usize,
+    height: usize,
+) -> Result<Vec<u8>, image::ImageError> {
+    if width
+        .checked_mul(height)
+        .map_or(true, |size| pixels.len() < size)
+    {
+        return Err(image::ImageError::Parameter(
+            image::error::ParameterError::from_kind(
+                image::error::ParameterErrorKind::DimensionMismatch,
+            ),
+        ));
+    }
+    let image = image::ImageBuffer::from_fn(width as u32, height as u32, |x, y| {
+        let (r, g, b, _a) = pixels[x as usize + y as usize * width].to_tuple();
+        image::Rgb([r, g, b])
+    });
+    let mut writer = std::io::Cursor::new(Vec::new());
+    image.write_to(&mut writer, image::ImageOutputFormat::Png)?;
+    Ok(writer.into_inner())
+}
+
+#[test]
+fn contrast_stretch_test() {
+    let mut pixels = (0..=100).map(|x| x as f32).collect::<Vec<_>>();
+    pixels.push(f32::NAN);
+    pixels.push(1e9);
+    let data = HeatmapData {
+        width: pixels.len() as i32,
+        height: 1,
+        pixels,
+    };
+    assert_eq!(data.contrast_stretch(0.), (0., 1e9));
+    let (lower, upper) = data.contrast_stretch(5.);
+    assert!((4. ..=6.).contains(&lower));
+    assert!((95. ..=97.).contains(&upper));
+    let empty = HeatmapData {
+        width: 1,
+        height: 1,
+        pixels: vec![f32::NAN],
+    };
+    assert_eq!(empty.contrast_stretch(1.), (0., 1.));
+}
+
+#[test]
+fn extrema_test() {
+    let data = HeatmapData {
+        width: 3,
+        height: 2,
+        pixels: vec![f32::NAN, 2., -1., 5., f32::INFINITY, -1.],
+    };
+    assert_eq!(
+        data.extrema(),
+        Some((BitMapPoint { x: 2, y: 0 }, BitMapPoint { x: 0, y: 1 }))
+    );
+    let empty = HeatmapData {
+        width: 1,
+        height: 1,
+        pixels: vec![f32::NAN],
+    };
+    assert_eq!(empty.extrema(), None);
+}
+
+#[test]
+fn equalize_test() {
+    let data = HeatmapData {
+        width: 5,
+        height: 1,
+        pixels: vec![1000., f32::NAN, 1., 2., 2.],
+    };
+    let equalized = data.equalize();
+    assert_eq!(equalized.pixels[0], 1.);
+    assert!(equalized.pixels[1].is_nan());
+    assert_eq!(equalized.pixels[2], 0.);
+    assert_eq!(equalized.pixels[3], 0.5);
+    assert_eq!(equalized.pixels[4], 0.5);
+}
+
+#[test]
+fn to_bitmap_with_mask_test() {
+    let data = HeatmapData {
+        width: 2,
+        height: 2,
+        pixels: vec![-1., 0.5, f32::NAN, 2.],
+    };
+    let (bitmap, mask) = data.to_bitmap_with_mask(
+        (0., 1.),
+        crate::colors::ColorGradientOptions::StartEnd {
+            start: egui::Color32::RED,
+            end: egui::Color32::BLUE,
+            steps: 8,
+        },
+        egui::Color32::BLACK,
+    );
+    assert_eq!(bitmap.pixels.len(), 4);
+    assert_eq!(
+        mask.pixels,
+        vec![
+            Clamped::Below,
+            Clamped::InRange,
+            Clamped::NoData,
+            Clamped::Above
+        ]
+    );
+    assert_eq!(
+        mask.get_at_point(BitMapPoint { x: 1, y: 1 }),
+        Some(Clamped::Above)
+    );
+    let text = mask.overlay_text(&crate::CoordinatePoint { x: 10, y: 20 }, "<", ">");
+    assert_eq!(text.len(), 2);
+    assert_eq!(text[&crate::CoordinatePoint { x: 10, y: 20 }], "<");
+    assert_eq!(text[&crate::CoordinatePoint { x: 11, y: 21 }], ">");
+}
+
+#[test]
+fn to_bitmap_with_gradient_test() {
+    let data = HeatmapData {
+        width: 4,
+        height: 1,
+        pixels: vec![-1., 0.5, f32::NAN, 2.],
+    };
+    let gradient = crate::colors::Gradient::from_colors(vec!['a', 'b', 'c']).unwrap();
+    let (bitmap, _) = data.to_bitmap_with_gradient((0., 1.), &gradient, '.');
+    assert_eq!(bitmap.pixels, vec!['a', 'b', '.', 'c']);
+    assert_eq!(
+        bitmap.get_color_at_point(BitMapPoint { x: 3, y: 0 }),
+        Some('c')
+    );
+    assert!(crate::colors::Gradient::<char>::from_colors(Vec::new()).is_none());
+}
+
+#[test]
+fn to_bitmap_scaled_test() {
+    let data = HeatmapData {
+        width: 5,
+        height: 1,
+        pixels: vec![1., 10., 100., 0., -5.],
+    };
+    let gradient = crate::colors::Gradient::from_colors(vec!['a', 'b', 'c']).unwrap();
+    let (bitmap, mask) = data
+        .to_bitmap_scaled_with_gradient((1., 100.), &gradient, '.', ScaleMode::Log10)
+        .unwrap();
+    assert_eq!(bitmap.pixels, vec!['a', 'b', 'c', '.', '.']);
+    assert_eq!(mask.pixels[3], Clamped::NoData);
+    assert_eq!(ScaleMode::Log10.apply(100.), 2.);
+    assert!((ScaleMode::Log10.invert(1.) - 10.).abs() < 1e-4);
+
+    let symlog = ScaleMode::SymLog { linthresh: 1. };
+    let (bitmap, _) = data
+        .to_bitmap_scaled_with_gradient((-100., 100.), &gradient, '.', symlog)
+        .unwrap();
+    assert_eq!(bitmap.pixels[3], 'b');
+    assert_eq!(bitmap.pixels[2], 'c');
+    assert_eq!(symlog.apply(-5.), -symlog.apply(5.));
+    assert!((symlog.invert(symlog.apply(-5.)) + 5.).abs() < 1e-4);
+    assert_eq!(ScaleMode::default(), ScaleMode::Linear);
+    // a non-positive linthresh is rejected, instead of making each value no-data
+    for linthresh in [0., -1., f32::NAN] {
+        let symlog = ScaleMode::SymLog { linthresh };
+        assert!(!symlog.is_valid());
+        assert!(data
+            .to_bitmap_scaled_with_gradient((-100., 100.), &gradient, '.', symlog)
+            .is_none());
+    }
+    // limits which cannot be scaled are rejected, instead of giving NaN colors
+    assert!(!ScaleMode::Log10.is_valid_limits((0., 100.)));
+    assert!(!ScaleMode::Linear.is_valid_limits((1., 1.)));
+    assert!(!ScaleMode::Linear.is_valid_limits((1., f32::NAN)));
+    assert!(ScaleMode::Log10.is_valid_limits((1., 100.)));
+    assert!(data
+        .to_bitmap_scaled_with_gradient((0., 100.), &gradient, '.', ScaleMode::Log10)
+        .is_none());
+    assert!(data
+        .to_bitmap_scaled_with_gradient((100., 1.), &gradient, '.', ScaleMode::Log10)
+        .is_none());
+}
+
+#[test]
+fn to_png_bytes_test() {
+    let bitmap = BitmapData {
+        width: 2,
+        height: 1,
+        pixels: vec![
+            egui::Color32::RED,
+            egui::Color32::from_rgba_premultiplied(0, 0, 255, 0),
+        ],
+    };
+    let png = bitmap.to_png_bytes().unwrap();
+    let decoded = image::load_from_memory_with_format(&png, image::ImageFormat::Png)
+        .unwrap()
+        .to_rgba8();
+    assert_eq!(decoded.dimensions(), (2, 1));
+    assert_eq!(decoded.get_pixel(0, 0).0, [255, 0, 0, 255]);
+    assert_eq!(decoded.get_pixel(1, 0).0, [0, 0, 255, 255]);
+    // too few colors for the size are an error, instead of a panic
+    let bitmap = BitmapData {
+        width: 2,
+        height: 2,
+        pixels: vec![egui::Color32::RED; 3],
+    };
+    assert!(matches!(
+        bitmap.to_png_bytes(),
+        Err(image::ImageError::Parameter(_))
+    ));
 }
@@ -1,25 +1,39 @@
 use std::fmt::Debug;
 
+use crate::bitmap_data::BitMapPoint;
 use crate::multimap::KeyBoardDirection;
 pub use crate::multimap::{
-    BitMapText, ColorWithThickness, CoordinatePoint, CoordinateRect, Data, FontOptions, Overlay,
-    RenderProblem,
+    ColorWithThickness, ColorbarFormatter, ColorbarLabels, ColorbarPlacement, CoordinatePoint,
+    CoordinateRect, Corner, Data, Downsampling, GridLines, Legend, Overlay, OverlayBuilder,
+    PanBounds, RelativeThickness, RenderProblem, ScaleBar, SelectionStyle, TextCompositing,
+    Upsampling,
 };
+use crate::FontOptions;
 use egui::Color32 as Color;
-use egui_extras::RetainedImage as RenderedImage;
 
-#[derive(serde::Deserialize, serde::Serialize, Default)]
+/// Texts shown by the widget, mostly in the context menu
+#[derive(serde::Deserialize, serde::Serialize, Default, Clone, Debug)]
 pub struct Localization {
-    text_copy_to_clipboard_delayed: String, //"Copy to Clipboard in 3 seconds"
-    text_copy_to_clipboard_instantly: String, //"Copy to Clipboard"
-    text_hide: String,                      //"Hide"
-    text_show_all: String,                  //"Show all"
-    text_unselect_all: String,              //"Unselect all"
-    text_home: String,                      //"Home"
+    /// Button to copy the image to the clipboard after a delay, e.g. "Copy to Clipboard in 3 seconds"
+    pub text_copy_to_clipboard_delayed: String,
+    /// Button to copy the image to the clipboard, e.g. "Copy to Clipboard"
+    pub text_copy_to_clipboard_instantly: String,
+    /// Button to hide the hovered dataset, e.g. "Hide"
+    pub text_hide: String,
+    /// Button to show all hidden datasets, e.g. "Show all"
+    pub text_show_all: String,
+    /// Button to clear the selection, e.g. "Unselect all"
+    pub text_unselect_all: String,
+    /// Button to reset the shown area, e.g. "Home"
+    pub text_home: String,
+    /// Label shown if there is no data at all, e.g. "No data". Use an empty string to show nothing
+    #[serde(default)]
+    pub text_no_data: String,
 }
 
 impl Localization {
-    fn english() -> Self {
+    /// English texts
+    pub fn english() -> Self {
         Self {
             text_copy_to_clipboard_delayed: "Copy to Clipboard in 3 seconds".to_string(),
             text_copy_to_clipboard_instantly: "Copy to Clipboard".to_string(),
@@ -27,6 +41,20 @@ impl Localization {
             text_show_all: "Show all".to_string(),
             text_unselect_all: "Unselect all".to_string(),
             text_home: "Home".to_string(),
+            text_no_data: "No data".to_string(),
+        }
+    }
+    /// German texts
+    pub fn german() -> Self {
+        Self {
+            text_copy_to_clipboard_delayed: "In 3 Sekunden in die Zwischenablage kopieren"
+                .to_string(),
+            text_copy_to_clipboard_instantly: "In die Zwischenablage kopieren".to_string(),
+            text_hide: "Ausblenden".to_string(),
+            text_show_all: "Alle anzeigen".to_string(),
+            text_unselect_all: "Auswahl aufheben".to_string(),
+            text_home: "Startansicht".to_string(),
+            text_no_data: "Keine Daten".to_string(),
         }
     }
 }
@@ -37,16 +65,27 @@ pub struct ShowState<Key: Eq + std::hash::Hash> {
     localization: Localization,
 
     mouse: MultiMapPosition<Key>,
-    clicked: bool, // Clicked plot can be fetched by mouse-value
+    clicked: bool,        // Clicked plot can be fetched by mouse-value
+    double_clicked: bool, // Double-clicked plot can be fetched by mouse-value
     render_problem: Option<RenderProblem>,
     events: Vec<Event<Key>>,
+    #[serde(skip)]
+    needs_rendering: bool,
+    #[serde(skip)]
+    home_requested: bool,
+    // the shown rectangle was set directly, so a running animation must not overwrite it
+    #[serde(skip)]
+    shown_set: bool,
 }
-/// Events which happend to the heatmap
-#[derive(Debug, serde::Deserialize, serde::Serialize)]
+/// Events which happend to the heatmap.
+/// Visibility events (Hide, Show, ShowAll) are emitted before a resulting 'ShowRectangle' event
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
 pub enum Event<Key> {
-    /// A dataset shall be hidden
+    /// A dataset was hidden
     Hide(Key),
-    /// All datasets shall be shown
+    /// A hidden dataset was shown again
+    Show(Key),
+    /// All datasets shall be shown. This is preceded by a 'Show' event for each previously hidden dataset
     ShowAll,
     /// All selected positions are cleared
     UnselectAll,
@@ -56,47 +95,172 @@ pub enum Event<Key> {
     /// The selection was changed
     /// The new selection can be fetched via 'selected'
     Selection,
+    /// A point was not selected, since 'max_selected' points are already selected
+    SelectionLimitReached,
+    /// The image was copied to the clipboard
+    CopiedToClipboard,
 }
 impl<Key: std::hash::Hash + Eq + Clone> ShowState<Key> {
     /// Select the given positions and only those
     pub fn make_selected(&mut self, selected: std::collections::HashSet<CoordinatePoint>) {
-        self.multimap.selected = selected;
+        self.replace_selected(selected);
     }
     /// Clear selected positions
     pub fn clear_selected(&mut self) {
-        self.multimap.selected.clear();
+        self.replace_selected(Default::default());
+    }
+    /// Undo the last change of the selection. Returns false if there is nothing to undo
+    pub fn undo_selection(&mut self) -> bool {
+        let changed = self.multimap.undo_selection();
+        if changed {
+            self.events.push(Event::Selection);
+            self.needs_rendering = true;
+        }
+        changed
+    }
+    /// Redo the last undone change of the selection. Returns false if there is nothing to redo
+    pub fn redo_selection(&mut self) -> bool {
+        let changed = self.multimap.redo_selection();
+        if changed {
+            self.events.push(Event::Selection);
+            self.needs_rendering = true;
+        }
+        changed
+    }
+    /// Check if a change of this state (e.g. of the selection) is not yet shown.
+    /// Apps only repainting on demand should call 'request_repaint' in this case
+    pub fn needs_repaint(&self) -> bool {
+        self.needs_rendering
+    }
+    /// Get the texts used in the context menu
+    pub fn localization(&self) -> &Localization {
+        &self.localization
+    }
+    /// Change the texts used in the context menu, e.g. to switch the language at runtime
+    pub fn set_localization(&mut self, localization: Localization) {
+        self.localization = localization;
     }
     /// Get events
     pub fn events(&mut self) -> Vec<Event<Key>> {
         std::mem::take(&mut self.events)
     }
+    /// Get the events without removing them, e.g. to log them while another part of the app handles them via 'events'
+    pub fn peek_events(&self) -> &[Event<Key>] {
+        &self.events
+    }
+    /// Get the navigation events (i.e. 'Event::ShowRectangle') only. All other events are kept for 'events'.
+    /// To link several widgets, copy 'currently_showing' to the other states via 'set_shown' if this is not empty
+    pub fn take_navigation_events(&mut self) -> Vec<Event<Key>> {
+        let (navigation, others) = std::mem::take(&mut self.events)
+            .into_iter()
+            .partition(|e| matches!(e, Event::ShowRectangle));
+        self.events = others;
+        navigation
+    }
+    /// The shown rectangle, if the view was changed (i.e. there was an 'Event::ShowRectangle') since the last call.
+    /// This removes the navigation events, all other events are kept for 'events'.
+    /// To link several widgets, apply the result to the other states via 'set_shown', see 'ShowStateSingle::take_view_change'
+    pub fn take_view_change(&mut self) -> Option<CoordinateRect> {
+        if self.take_navigation_events().is_empty() {
+            None
+        } else {
+            self.currently_showing()
+        }
+    }
     /// Get the currently selected points
     pub fn selected(&self) -> &std::collections::HashSet<CoordinatePoint> {
         &self.multimap.selected
     }
+    /// Get the currently selected points, sorted by row and then by column.
+    /// In contrast to 'selected', the order is stable, e.g. for showing or exporting the selection
+    pub fn selected_sorted(&self) -> Vec<CoordinatePoint> {
+        let mut selected = self.multimap.selected.iter().cloned().collect::<Vec<_>>();
+        selected.sort_by_key(|CoordinatePoint { x, y }| (*y, *x));
+        selected
+    }
     /// Fetch rectangle which is currently shown
     pub fn currently_showing(&self) -> Option<CoordinateRect> {
         self.multimap.currently_showing()
     }
+    /// Show the given rectangle, e.g. to restore a saved view or to mirror another widget.
+    /// A running animation (see 'animation_duration') is cancelled during the next 'ui' call.
+    /// This does not emit 'Event::ShowRectangle', so copying the rectangle between linked widgets cannot cause a feedback loop.
+    /// Returns false (and changes nothing) if the rectangle does not contain any point.
+    /// This is the setter of the shown rectangle, i.e. the counterpart of 'currently_showing'
+    #[doc(alias = "set_shown_rectangle")]
+    pub fn set_shown(&mut self, rect: CoordinateRect) -> bool {
+        let changed = self.multimap.set_shown(&rect);
+        if changed {
+            self.shown_set = true;
+            self.needs_rendering = true;
+        }
+        changed
+    }
+    /// Show the bounding box of all shown data sets during the next 'ui' call, like the 'Home' key does.
+    /// In contrast to 'MultiBitmapWidget::home', this only needs the state, e.g. for a "Reset view" button, and emits 'Event::ShowRectangle'
+    pub fn request_home(&mut self) {
+        self.home_requested = true;
+        self.needs_rendering = true;
+    }
+    /// Zoom to the bounding box of the selected points, expanded by a small margin.
+    /// Emits 'Event::ShowRectangle'. Returns false (and changes nothing) if nothing is selected
+    pub fn zoom_to_selection(&mut self) -> bool {
+        let changed = self.multimap.zoom_to_selection();
+        if changed {
+            self.events.push(Event::ShowRectangle);
+            self.shown_set = true;
+            self.needs_rendering = true;
+        }
+        changed
+    }
     /// Check if there was an issue will rendering
     pub fn render_problem(&self) -> Option<&RenderProblem> {
         self.render_problem.as_ref()
     }
+    /// Forget the current render problem. Note that each successful render clears it, too
+    pub fn clear_render_problem(&mut self) {
+        self.render_problem = None;
+    }
     /// Check if position was clicked
     pub fn clicked(&self) -> Option<&MultiMapPosition<Key>> {
         self.clicked.then_some(&self.mouse)
     }
+    /// Check if position was double-clicked
+    pub fn double_clicked(&self) -> Option<&MultiMapPosition<Key>> {
+        self.double_clicked.then_some(&self.mouse)
+    }
     /// Check if position was clicked
     pub fn hover(&self) -> &MultiMapPosition<Key> {
         &self.mouse
     }
 
+    /// Unselect all points outside of the given rectangle. Emits 'Event::Selection' if anything was unselected.
+    /// Returns true if the selection changed
+    pub fn prune_selection_to(&mut self, rect: &CoordinateRect) -> bool {
+        self.retain_selected(|point| rect.contains(point))
+    }
+    /// Unselect all points outside of the given rectangle, like 'prune_selection_to', but without storing an undo step.
+    /// Otherwise, undoing would restore points which are immediately clipped again
+    fn clip_selection_to(&mut self, rect: &CoordinateRect) {
+        let count = self.multimap.selected.len();
+        self.multimap.selected.retain(|point| rect.contains(point));
+        if self.multimap.selected.len() != count {
+            self.events.push(Event::Selection);
+            self.needs_rendering = true;
+        }
+    }
+    /// Keep only selected points fulfilling the predicate. Returns true if the selection changed
+    pub(crate) fn retain_selected(&mut self, f: impl Fn(&CoordinatePoint) -> bool) -> bool {
+        let selected = self.multimap.selected.iter().filter(|x| f(x)).cloned();
+        self.change_selected(selected.collect())
+    }
+
     fn has_hidden(&self) -> bool {
         self.multimap.to_plot.iter().any(|(_, &b)| !b)
     }
 
-    fn can_hide(&self) -> bool {
-        self.multimap.to_plot.iter().filter(|(_, &b)| b).count() > 1
+    fn can_hide(&self, key: &Key) -> bool {
+        !self.is_pinned(key) && self.multimap.to_plot.iter().filter(|(_, &b)| b).count() > 1
     }
 
     fn hide(&mut self, key: &Key) {
@@ -109,21 +273,59 @@ impl<Key: std::hash::Hash + Eq + Clone> ShowState<Key> {
     }
 
     fn show_all(&mut self) {
+        for (key, p) in self.multimap.to_plot.iter_mut() {
+            if !*p {
+                *p = true;
+                self.events.push(Event::Show(key.clone()));
+            }
+        }
         self.events.push(Event::ShowAll);
-        self.multimap
-            .to_plot
-            .iter_mut()
-            .for_each(|(_, p)| *p = true)
     }
 
-    fn unselect_all(&mut self) -> bool {
-        self.events.push(Event::UnselectAll);
-        if self.multimap.selected.is_empty() {
-            false
+    /// Check if the dataset with the given key is shown
+    pub fn is_visible(&self, key: &Key) -> bool {
+        self.multimap.to_plot.get(key).cloned().unwrap_or(true)
+    }
+    /// Show or hide the dataset with the given key, emitting 'Event::Show' or 'Event::Hide'.
+    /// Returns false if nothing changed or the key is unknown. Pinned datasets and the last shown dataset cannot be hidden
+    pub fn set_visible(&mut self, key: &Key, visible: bool) -> bool {
+        if !self.multimap.to_plot.contains_key(key)
+            || self.is_visible(key) == visible
+            || (!visible && !self.can_hide(key))
+        {
+            return false;
+        }
+        if visible {
+            self.multimap.to_plot.insert(key.clone(), true);
+            self.events.push(Event::Show(key.clone()));
         } else {
-            self.multimap.selected.clear();
-            true
+            self.hide(key);
+        }
+        self.needs_rendering = true;
+        true
+    }
+
+    /// Check if the dataset with the given key is pinned, i.e. cannot be hidden
+    pub fn is_pinned(&self, key: &Key) -> bool {
+        self.multimap.pinned.contains(key)
+    }
+    /// Pin the dataset with the given key, so that it cannot be hidden (e.g. a reference layer), or unpin it.
+    /// This does not change whether the dataset is currently shown. Returns false if nothing changed or the key is unknown
+    pub fn set_pinned(&mut self, key: &Key, pinned: bool) -> bool {
+        if !self.multimap.to_plot.contains_key(key) || self.is_pinned(key) == pinned {
+            return false;
+        }
+        if pinned {
+            self.multimap.pinned.insert(key.clone());
+        } else {
+            self.multimap.pinned.remove(key);
         }
+        true
+    }
+
+    fn unselect_all(&mut self) -> bool {
+        self.events.push(Event::UnselectAll);
+        self.replace_selected(Default::default())
     }
 
     fn change_rect(&mut self) -> &mut crate::multimap::ShowRect {
@@ -133,9 +335,25 @@ impl<Key: std::hash::Hash + Eq + Clone> ShowState<Key> {
             .expect("'Render' has to be called before this")
     }
 
-    fn change_selected(&mut self) -> &mut std::collections::HashSet<CoordinatePoint> {
-        self.events.push(Event::Selection);
-        &mut self.multimap.selected
+    /// Replace the selection, storing the previous one for undo.
+    /// Returns false (and stores nothing) if the selection does not change
+    fn replace_selected(&mut self, selected: std::collections::HashSet<CoordinatePoint>) -> bool {
+        if self.multimap.selected == selected {
+            false
+        } else {
+            self.multimap.push_selection_history();
+            self.multimap.selected = selected;
+            self.needs_rendering = true;
+            true
+        }
+    }
+    /// Like 'replace_selected', but emits 'Event::Selection' if the selection changed
+    fn change_selected(&mut self, selected: std::collections::HashSet<CoordinatePoint>) -> bool {
+        let changed = self.replace_selected(selected);
+        if changed {
+            self.events.push(Event::Selection);
+        }
+        changed
     }
 
     fn get_inner_mut(&mut self) -> &mut crate::multimap::MultimapState<Key> {
@@ -153,7 +371,7 @@ pub enum MultiMapPosition<Key> {
     /// Mouse is hovering over data area, containing the point in data coordinates
     Pixel(Key, CoordinatePoint),
     /// Mouse is over Colorbar
-    Colorbar(f32),
+    Colorbar(f64),
 }
 
 impl<Key> MultiMapPosition<Key> {
@@ -182,49 +400,261 @@ pub struct MultiBitmapWidget<Key> {
     // size
     current_size: [f32; 2],
     dynamic_resizing: bool,
+    keep_aspect_ratio: bool,
+    letterbox_color: Option<Color>,
     // egui
-    rendered_image: RenderedImage,
+    // all subplots, boundaries and the colorbar share this single texture, see 'render'
+    rendered_image: Option<egui::TextureHandle>,
+    last_rendered: Option<egui::ColorImage>,
+    last_image_rect: Option<egui::Rect>,
     debug_name: String,
+    texture_options: egui::TextureOptions,
+    placeholder_color: Color,
     needs_rendering: bool,
     // interaction
     copy_to_clipboard_delay: Option<(std::time::Instant, [f32; 2])>,
+    clipboard_in_background: bool,
+    copy_shortcut: Option<egui::KeyboardShortcut>,
+    clipboard_pending: Option<std::sync::mpsc::Receiver<Result<(), RenderProblem>>>,
     hide_key: Option<Key>,
+    navigation: NavigationConfig,
+    double_click_recenters: bool,
+    recenter_snaps_to_data: bool,
+    double_click_callback: Option<DoubleClickCallback<Key>>,
+    value_lookup: Option<ValueLookup<Key>>,
+    // numeric data given to 'from_heatmaps'
+    source_values: Vec<SourceValues<Key>>,
+    // animation
+    animation_duration: Option<std::time::Duration>,
+    animation: Option<ShowRectAnimation>,
+    // scrolling
+    horizontal_scroll_factor: f32,
+    scroll_zoom_sensitivity: f32,
+    shift_scroll_pans: bool,
+    // selection
+    max_selected: Option<usize>,
+    clip_selection_to_view: bool,
+    colorbar_marker: Option<Color>,
+    extrema_markers: Option<ExtremaMarkers>,
+}
+type DoubleClickCallback<Key> = Box<dyn FnMut(&MultiMapPosition<Key>)>;
+/// Numeric data given to 'from_heatmaps', together with the locations of its extrema
+struct SourceValues<Key> {
+    key: Key,
+    heatmap: crate::HeatmapData,
+    extrema: Option<(BitMapPoint, BitMapPoint)>,
+}
+type ValueLookup<Key> = Box<dyn Fn(&Key, &CoordinatePoint) -> Option<f64>>;
+
+struct ShowRectAnimation {
+    from: crate::multimap::ShowRect,
+    to: crate::multimap::ShowRect,
+    start: std::time::Instant,
+    duration: std::time::Duration,
+}
+
+/// Which navigation gestures are handled by the widget
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct NavigationConfig {
+    /// Zoom via scrolling and the keys '+' and '-'
+    pub zoom_enabled: bool,
+    /// Pan via the arrow keys and shift+scroll (see 'shift_scroll_pans'), and re-center via double click
+    pub pan_enabled: bool,
+    /// Handle keys at all (arrows, '+', '-', Home). Like the context menu entry, Home needs zoom or pan enabled
+    pub keyboard_enabled: bool,
+    /// Zoom into a rectangle by dragging
+    pub drag_zoom_enabled: bool,
+    /// Select points by clicking
+    pub selection_enabled: bool,
+}
+impl Default for NavigationConfig {
+    fn default() -> Self {
+        Self {
+            zoom_enabled: true,
+            pan_enabled: true,
+            keyboard_enabled: true,
+            drag_zoom_enabled: true,
+            selection_enabled: true,
+        }
+    }
+}
+impl NavigationConfig {
+    /// Disable all gestures, e.g. for a static heatmap
+    pub fn disabled() -> Self {
+        Self {
+            zoom_enabled: false,
+            pan_enabled: false,
+            keyboard_enabled: false,
+            drag_zoom_enabled: false,
+            selection_enabled: false,
+        }
+    }
 }
 
-/// This is the main settings type
+/// Markers for the smallest and the largest value of a data set, drawn on top of the image
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct ExtremaMarkers {
+    /// Color of the diagonal cross marking the smallest value
+    pub min_color: Color,
+    /// Color of the upright cross marking the largest value
+    pub max_color: Color,
+    /// Size of the crosses in points
+    pub size: f32,
+}
+
+/// This is the main settings type.
+/// All settings can be serialized, except for 'colorbar_formatter' and 'copy_shortcut', which are None after deserialization
+#[derive(serde::Deserialize, serde::Serialize)]
 pub struct MultiBitmapWidgetSettings {
     // egui
     /// Size of the render area.
     /// Use 'None' to request all available space
     pub start_size: Option<[f32; 2]>,
+    /// Only used if 'start_size' is None: Use all available width, but choose the height such that the data is not stretched.
+    /// If there is not enough height available, the width is reduced instead
+    pub keep_aspect_ratio: bool,
+    /// Only used if 'keep_aspect_ratio' is set: Fill the remaining available space with this color and center the plot in it, e.g. 'background'.
+    /// Use 'None' to shrink the widget to the plot instead
+    pub letterbox_color: Option<Color>,
     /// id of this plot - needs to be locally unique (this is an egui-ID)
     pub id: String,
+    /// Texture filtering, used if the image is drawn at a different size than rendered.
+    /// Use 'egui::TextureOptions::NEAREST' to keep pixels sharp. The default (linear) may blur them
+    pub texture_options: egui::TextureOptions,
+    /// Color of the image shown in case of a render problem, see 'ShowState::render_problem'
+    pub placeholder_color: Color,
     // ShowMultiMapSettings
     /// Shall there be a boundary line between two data samples?
     pub boundary_between_data: ColorWithThickness<Color>,
     /// Shall there be a color bar?
-    pub colorbar: Option<(crate::colors::Gradient<Color>, usize, (f32, f32))>,
+    pub colorbar: Option<(crate::colors::Gradient<Color>, usize, (f64, f64))>,
     /// Background color
     pub background: Color,
+    /// Color of data points without value (e.g. NaN) in the data given to 'from_heatmaps'.
+    /// Use a color different from 'background' to distinguish them from areas without data points. None: 'background'
+    pub missing_data_color: Option<Color>,
     /// Boundary color for unselected points
     pub boundary_unselected: ColorWithThickness<Color>,
     /// Boundary color for selected points
     pub boundary_selected: Color,
     /// Minimimum ratio of pixels per point by boundary thickness to draw the boundary
     pub boundary_factor_min: usize,
+    /// How to combine data points if there are more data points than pixels
+    pub downsampling: Downsampling,
+    /// Shall there be a scale bar in each plot?
+    pub scale_bar: Option<ScaleBar<Color>>,
+    /// Shall there be grid lines?
+    pub grid: Option<GridLines<Color>>,
+    /// If a title does not fit even at the smallest font size, shall it be truncated with an ellipsis?
+    /// Otherwise, the title is not drawn
+    pub truncate_titles: bool,
+    /// Number format of the colorbar labels
+    pub colorbar_labels: ColorbarLabels,
+    /// Thickness of the colorbar relative to the widget width. If set, this overrides the thickness given in 'colorbar'
+    pub colorbar_relative_thickness: Option<RelativeThickness>,
+    /// Width of the separator between plots and colorbar, in pixels.
+    /// Use 'None' to use the thickness of 'boundary_between_data'
+    pub colorbar_gap: Option<usize>,
+    /// Brightness factor of the data inside the drag rectangle (zoom/selection preview), e.g. 0.5
+    pub drag_dim_factor: f32,
+    /// Outline of the drag rectangle (zoom/selection preview), drawn along the boundaries of the data points
+    pub drag_outline: Option<ColorWithThickness<Color>>,
+    /// How selected data points are highlighted, using the color 'boundary_selected'
+    pub selection_style: SelectionStyle,
+    /// Font of the colorbar labels. Use 'None' to use the font of the first data set
+    pub colorbar_font: Option<FontOptions>,
+    /// Titles and colorbar labels are shrunk to fit, while their font height stays above this, e.g. 8.0.
+    /// The height is in pixels of the rendered image, not in DPI-scaled points.
+    /// Labels which do not fit are truncated (titles, see 'truncate_titles') or not shown
+    pub min_font_height: f32,
+    /// How texts are drawn onto the image. 'Additive' suits dark color maps, 'Over' with a dark text color suits light ones
+    pub text_compositing: TextCompositing<Color>,
+    /// Legend explaining the colors of the selection boundaries and of areas without data, drawn once over the plots. Use 'None' for no legend
+    pub legend: Option<Legend>,
+    /// Number of labelled ticks on the colorbar, at least 2
+    pub colorbar_ticks: usize,
+    /// Side of the image where the colorbar is drawn
+    pub colorbar_placement: ColorbarPlacement,
+    /// Custom format of the colorbar labels (e.g. with units), which replaces 'colorbar_labels'.
+    /// The font is still shrunk (see 'min_font_height') if the label does not fit. None after deserialization
+    #[serde(skip)]
+    pub colorbar_formatter: Option<ColorbarFormatter>,
+    /// Zooming in stops at this number of shown data points per direction (None: at about 3 points)
+    pub min_shown_extent: Option<i32>,
+    /// Zooming out stops at this number of shown data points per direction (None: unlimited).
+    /// E.g. use the data extent plus a margin, such that a small map is not zoomed out to a single pixel
+    pub max_shown_extent: Option<i32>,
+    /// Keep part of the data visible while panning (None: panning is unlimited)
+    pub pan_bounds: Option<PanBounds>,
+    /// How to color the pixels if there are more pixels than data points
+    pub upsampling: Upsampling,
+    /// Scale of the colorbar (e.g. logarithmic), matching the scale used to convert the data to colors.
+    /// The colorbar limits, the labels and hovered colorbar values are in data units
+    pub colorbar_scale: crate::ScaleMode,
+    // interaction
+    /// Which navigation gestures are enabled
+    pub navigation: NavigationConfig,
+    /// Shall a double click center the view to the clicked point?
+    /// Set to false if double clicks are handled via 'set_double_click_callback' only
+    pub double_click_recenters: bool,
+    /// If true, re-centering via double click keeps as much data as possible in view.
+    /// So double clicking near the boundary of the data does not show mostly background
+    pub recenter_snaps_to_data: bool,
+    /// Duration of the animated transition for home, zoom-to-rectangle and re-centering.
+    /// Use 'None' to jump instantly. Scrolling, dragging and keyboard navigation are never animated
+    pub animation_duration: Option<std::time::Duration>,
+    /// Multiplier applied to the horizontal scroll delta while shift is pressed.
+    /// Many mice/trackpads report small horizontal deltas, so this is typically larger than 1, e.g. 5.0
+    pub horizontal_scroll_factor: f32,
+    /// Mouse-wheel scroll distance (in points) per zoom step, e.g. 50.0.
+    /// Increase it if zooming is too fast (e.g. with a trackpad). Values below 1.0 are treated as 1.0.
+    /// Zoom gestures (pinch, ctrl+scroll) use the same sensitivity
+    pub scroll_zoom_sensitivity: f32,
+    /// If true, shift+scroll pans horizontally. Otherwise, shift+scroll zooms like vertical scrolling
+    pub shift_scroll_pans: bool,
+    /// Maximal number of selected points. Use 'None' for no limit.
+    /// Once the limit is reached, further points are not added (existing points are kept) and 'Event::SelectionLimitReached' is emitted
+    pub max_selected: Option<usize>,
+    /// If true, selected points outside of the shown area (e.g. after zooming in) are unselected.
+    /// Otherwise, they are kept, even if they are not visible
+    pub clip_selection_to_view: bool,
+    /// Color of a marker line drawn on the colorbar at the hovered value. Use 'None' for no marker.
+    /// The marker is painted on top of the image, so moving it does not require rendering again
+    pub colorbar_marker: Option<Color>,
+    /// Mark the smallest and the largest value of each data set given to 'from_heatmaps'. Use 'None' for no markers
+    pub extrema_markers: Option<ExtremaMarkers>,
+    /// Encode and copy the image to the clipboard on a background thread, so the UI stays responsive.
+    /// On wasm, copying is always done synchronously
+    pub clipboard_in_background: bool,
+    /// Keyboard shortcut copying the image to the clipboard, while the widget is hovered and no other widget has the keyboard focus.
+    /// Use 'None' to copy via the context menu only
+    #[serde(skip)]
+    pub copy_shortcut: Option<egui::KeyboardShortcut>,
 }
 const COPY_CLIPBOARD_DELAY: std::time::Duration = std::time::Duration::from_secs(3);
+const CLIPBOARD_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+/// Scroll distance (in points) per factor e of a zoom gesture; egui reports ctrl+scroll as zoom by 'exp(delta / 200)'.
+/// Pinch and ctrl+scroll are converted back to this distance, so they follow 'scroll_zoom_sensitivity' like the mouse wheel
+const ZOOM_GESTURE_POINTS: f32 = 200.;
 
 impl<Key: std::hash::Hash + Clone + Eq + Debug> MultiBitmapWidget<Key> {
     /// Get default state, in english
     pub fn default_state_english(&self) -> ShowState<Key> {
+        self.default_state(Localization::english())
+    }
+    /// Get default state, using the given texts
+    pub fn default_state(&self, localization: Localization) -> ShowState<Key> {
         ShowState {
             multimap: self.showmap.default_state(),
-            localization: Localization::english(),
+            localization,
             mouse: MultiMapPosition::NotHovering,
             clicked: Default::default(),
+            double_clicked: Default::default(),
             render_problem: Default::default(),
             events: Default::default(),
+            needs_rendering: false,
+            home_requested: false,
+            shown_set: false,
         }
     }
     /// Main Constructor. This assumes that the data coordinates are linearly and axis-aligned to the bitmap, but the left-top corner can be adjusted for each subplot
@@ -234,13 +664,54 @@ impl<Key: std::hash::Hash + Clone + Eq + Debug> MultiBitmapWidget<Key> {
     ) -> Self {
         let MultiBitmapWidgetSettings {
             start_size,
+            keep_aspect_ratio,
+            letterbox_color,
             id: debug_name,
+            texture_options,
+            placeholder_color,
             boundary_between_data,
             colorbar,
             background,
+            // only used by 'from_heatmaps'
+            missing_data_color: _,
             boundary_unselected,
             boundary_selected,
             boundary_factor_min,
+            downsampling,
+            scale_bar,
+            grid,
+            truncate_titles,
+            colorbar_labels,
+            colorbar_relative_thickness,
+            colorbar_gap,
+            drag_dim_factor,
+            drag_outline,
+            selection_style,
+            colorbar_font,
+            min_font_height,
+            text_compositing,
+            legend,
+            colorbar_ticks,
+            colorbar_placement,
+            colorbar_formatter,
+            min_shown_extent,
+            max_shown_extent,
+            pan_bounds,
+            upsampling,
+            colorbar_scale,
+            navigation,
+            double_click_recenters,
+            recenter_snaps_to_data,
+            animation_duration,
+            horizontal_scroll_factor,
+            scroll_zoom_sensitivity,
+            shift_scroll_pans,
+            max_selected,
+            clip_selection_to_view,
+            colorbar_marker,
+            extrema_markers,
+            clipboard_in_background,
+            copy_shortcut,
         } = settings;
         Self {
             showmap: crate::multimap::ShowMultiMap::with_settings(
@@ -254,19 +725,347 @@ impl<Key: std::hash::Hash + Clone + Eq + Debug> MultiBitmapWidget<Key> {
                     boundary_unselected,
                     boundary_selected,
                     boundary_factor_min,
+                    downsampling,
+                    scale_bar,
+                    grid,
+                    truncate_titles,
+                    colorbar_labels,
+                    colorbar_relative_thickness,
+                    colorbar_gap,
+                    drag_dim_factor,
+                    drag_outline,
+                    selection_style,
+                    colorbar_font,
+                    min_font_height,
+                    text_compositing,
+                    legend,
+                    colorbar_ticks,
+                    colorbar_placement,
+                    colorbar_formatter,
+                    min_shown_extent,
+                    max_shown_extent,
+                    pan_bounds,
+                    upsampling,
+                    colorbar_scale,
                 },
             ),
             current_size: start_size.unwrap_or_default(),
             dynamic_resizing: start_size.is_none(),
-            rendered_image: RenderedImage::from_color_image(
-                debug_name.clone(),
-                egui::ColorImage::new([3, 3], Color::GOLD),
-            ),
+            keep_aspect_ratio,
+            letterbox_color,
+            rendered_image: None,
+            last_rendered: None,
+            last_image_rect: None,
             needs_rendering: true,
             debug_name,
+            texture_options,
+            placeholder_color,
             hide_key: None,
             copy_to_clipboard_delay: None,
+            clipboard_in_background,
+            copy_shortcut,
+            clipboard_pending: None,
+            navigation,
+            double_click_recenters,
+            recenter_snaps_to_data,
+            double_click_callback: None,
+            value_lookup: None,
+            source_values: Vec::new(),
+            animation_duration,
+            animation: None,
+            horizontal_scroll_factor,
+            scroll_zoom_sensitivity,
+            shift_scroll_pans,
+            max_selected,
+            clip_selection_to_view,
+            colorbar_marker,
+            extrema_markers,
+        }
+    }
+    /// Constructor using numeric data, which is converted to colors using the gradient and the limits (see 'HeatmapData::to_bitmap').
+    /// Each data set starts at coordinate (0, 0) and gets an overlay without texts, using the given font for the coordinates.
+    /// If the settings contain a colorbar, its gradient and limits are replaced by the given ones.
+    /// The data is scaled (e.g. logarithmic) before the conversion, and the colorbar uses the same scale (see 'HeatmapData::to_bitmap_scaled').
+    /// The values are kept for 'value_at' and the colorbar marker, which costs 4 bytes per data point in addition to the colors
+    /// Returns None if the scale or the limits are invalid (see 'ScaleMode::is_valid_limits') or the gradient has no colors (see 'Gradient::try_with_options')
+    pub fn from_heatmaps(
+        data: Vec<(Key, crate::HeatmapData)>,
+        limits: (f32, f32),
+        gradient: crate::colors::ColorGradientOptions,
+        scale: crate::ScaleMode,
+        font: FontOptions,
+        mut settings: MultiBitmapWidgetSettings,
+    ) -> Option<Self> {
+        if !scale.is_valid_limits(limits) {
+            return None;
+        }
+        let gradient_options = gradient;
+        let gradient = crate::colors::Gradient::try_with_options(&gradient_options)?;
+        if let Some((colorbar_gradient, _, colorbar_limits)) = &mut settings.colorbar {
+            *colorbar_gradient = crate::colors::Gradient::with_options(&gradient_options);
+            *colorbar_limits = (limits.0 as f64, limits.1 as f64);
+        }
+        settings.colorbar_scale = scale;
+        let missing_data = settings.missing_data_color.unwrap_or(settings.background);
+        let bitmaps = data
+            .iter()
+            .map(|(key, heatmap)| {
+                let (bitmap, _) = heatmap.to_bitmap_scaled_with_gradient(
+                    limits,
+                    &gradient,
+                    missing_data,
+                    scale,
+                )?;
+                Some((
+                    key.clone(),
+                    bitmap.into_data(CoordinatePoint { x: 0, y: 0 }, font.clone(), true),
+                ))
+            })
+            .collect::<Option<_>>()?;
+        let mut widget = Self::with_settings(bitmaps, settings);
+        widget.source_values = data
+            .into_iter()
+            .map(|(key, heatmap)| SourceValues {
+                extrema: heatmap.extrema(),
+                key,
+                heatmap,
+            })
+            .collect();
+        Some(widget)
+    }
+    /// Get the numeric value of a data point, if the data set was given to 'from_heatmaps' and has a value at this point.
+    /// Data points without value (e.g. NaN) are hovered as 'MultiMapPosition::Pixel', but give None here, e.g. to show "no value"
+    /// Replacing a data set via 'replace_data' removes its values
+    pub fn value_at(&self, key: &Key, point: &CoordinatePoint) -> Option<f32> {
+        let values = self
+            .source_values
+            .iter()
+            .find(|values| &values.key == key)?;
+        values
+            .heatmap
+            .get_data_at_point(BitMapPoint {
+                x: point.x,
+                y: point.y,
+            })
+            .filter(|value| value.is_finite())
+    }
+    /// Set a callback, which is invoked with the hovered position on each double click.
+    /// This is called in addition to re-centering, unless 'double_click_recenters' is disabled
+    pub fn set_double_click_callback(
+        &mut self,
+        callback: impl FnMut(&MultiMapPosition<Key>) + 'static,
+    ) {
+        self.double_click_callback = Some(Box::new(callback));
+    }
+    /// Remove the double click callback
+    pub fn clear_double_click_callback(&mut self) {
+        self.double_click_callback = None;
+    }
+    /// Set a function returning the value of a data point, if any.
+    /// While a data point is hovered, its value is marked on the colorbar (using the color 'colorbar_marker').
+    /// For data sets given to 'from_heatmaps', the kept values are used if the lookup returns None
+    pub fn set_value_lookup(
+        &mut self,
+        lookup: impl Fn(&Key, &CoordinatePoint) -> Option<f64> + 'static,
+    ) {
+        self.value_lookup = Some(Box::new(lookup));
+    }
+    /// Remove the value lookup
+    pub fn clear_value_lookup(&mut self) {
+        self.value_lookup = None;
+    }
+    /// Check if a change (e.g. new data, an animation) is not yet shown.
+    /// Apps only repainting on demand should call 'request_repaint' in this case.
+    /// The flag is cleared when the image is rendered. See also 'ShowState::needs_repaint'
+    pub fn needs_repaint(&self) -> bool {
+        self.needs_rendering || self.animation.is_some()
+    }
+    /// Change which navigation gestures are enabled
+    pub fn set_navigation(&mut self, navigation: NavigationConfig) {
+        self.navigation = navigation;
+    }
+    /// Get which navigation gestures are enabled
+    pub fn navigation(&self) -> NavigationConfig {
+        self.navigation
+    }
+    /// Show the bounding box of all shown data sets, like the 'Home' key does.
+    /// In contrast to the key, this is not animated. Emits 'Event::ShowRectangle' if the shown rectangle changes
+    pub fn home(&mut self, state: &mut ShowState<Key>) {
+        let before = state.multimap.shown_rectangle.clone();
+        self.animation = None;
+        self.showmap.home(state.get_inner_mut());
+        self.needs_rendering = true;
+        self.shown_changed(before, state);
+    }
+    /// Zoom in (positive increment) or out (negative increment) by the given number of points per side, like the '+'/'-' keys do.
+    /// This is not animated. Emits 'Event::ShowRectangle' if the shown rectangle changes. Nothing happens before the first render
+    pub fn zoom(&mut self, increment: i32, state: &mut ShowState<Key>) {
+        let before = state.multimap.shown_rectangle.clone();
+        if let Some(shown_rectangle) = &mut state.multimap.shown_rectangle {
+            self.animation = None;
+            self.showmap.zoom([increment, increment], shown_rectangle);
+            self.needs_rendering = true;
+        }
+        self.shown_changed(before, state);
+    }
+    /// Move the shown rectangle by the given number of points, like dragging does.
+    /// This is not animated. Emits 'Event::ShowRectangle' if the shown rectangle changes. Nothing happens before the first render
+    pub fn translate(&mut self, delta: CoordinatePoint, state: &mut ShowState<Key>) {
+        let before = state.multimap.shown_rectangle.clone();
+        if let Some(shown_rectangle) = &mut state.multimap.shown_rectangle {
+            self.animation = None;
+            self.showmap.translate(delta, shown_rectangle);
+            self.needs_rendering = true;
+        }
+        self.shown_changed(before, state);
+    }
+    /// Center the shown rectangle at the given point, keeping its size, like double-clicking does.
+    /// This is not animated. Emits 'Event::ShowRectangle' if the shown rectangle changes. Nothing happens before the first render
+    pub fn center_to(&mut self, point: &CoordinatePoint, state: &mut ShowState<Key>) {
+        let before = state.multimap.shown_rectangle.clone();
+        if let Some(shown_rectangle) = &mut state.multimap.shown_rectangle {
+            self.animation = None;
+            self.showmap.center_to(point, shown_rectangle);
+            self.needs_rendering = true;
+        }
+        self.shown_changed(before, state);
+    }
+    /// Emit 'Event::ShowRectangle' if the shown rectangle differs from the given one
+    fn shown_changed(&self, before: Option<crate::multimap::ShowRect>, state: &mut ShowState<Key>) {
+        if state.multimap.shown_rectangle != before {
+            state.events.push(Event::ShowRectangle);
+        }
+    }
+    /// Get the bounding box of all shown data sets, i.e. the area shown after 'Home'.
+    /// Returns None if no data set is shown
+    pub fn data_bounds(&self, state: &ShowState<Key>) -> Option<CoordinateRect> {
+        self.showmap.data_bounds(&state.multimap)
+    }
+    /// Select all points of the given row (shift+click), in all shown data sets covering this row.
+    /// Since the selection is shared by all data sets, the points are highlighted in each data set containing them.
+    /// If 'additive' is false, the previous selection is cleared. 'max_selected' is respected
+    pub fn select_row(&self, y: i32, additive: bool, state: &mut ShowState<Key>) {
+        let points = self.showmap.row_points(y, &state.multimap);
+        self.select_points(points, additive, state);
+    }
+    /// Select all points of the given column (alt+click), in all shown data sets covering this column.
+    /// Since the selection is shared by all data sets, the points are highlighted in each data set containing them.
+    /// If 'additive' is false, the previous selection is cleared. 'max_selected' is respected
+    pub fn select_column(&self, x: i32, additive: bool, state: &mut ShowState<Key>) {
+        let points = self.showmap.column_points(x, &state.multimap);
+        self.select_points(points, additive, state);
+    }
+    fn select_points(
+        &self,
+        points: Vec<CoordinatePoint>,
+        additive: bool,
+        state: &mut ShowState<Key>,
+    ) {
+        let limit = self.max_selected.unwrap_or(usize::MAX);
+        let mut selected = if additive {
+            state.multimap.selected.clone()
+        } else {
+            Default::default()
+        };
+        let mut limit_reached = false;
+        for point in points {
+            if selected.len() >= limit && !selected.contains(&point) {
+                limit_reached = true;
+                break;
+            }
+            selected.insert(point);
+        }
+        state.change_selected(selected);
+        if limit_reached {
+            state.events.push(Event::SelectionLimitReached);
+        }
+    }
+    /// Get the size in pixels ([width, height]) the widget renders at, including colorbar and boundaries.
+    /// This is the size of the most recent call of 'ui' (or the start size)
+    pub fn effective_render_size(&self) -> [usize; 2] {
+        let [width, height] = self.current_size;
+        [width as usize, height as usize]
+    }
+    /// Get the size in pixels ([width, height]) of each subplot when rendering at 'effective_render_size'.
+    /// Returns None if nothing fits, e.g. if the widget is smaller than the colorbar
+    pub fn plot_area_size(&self, state: &ShowState<Key>) -> Option<[usize; 2]> {
+        self.showmap
+            .plot_area_size(self.effective_render_size(), &state.multimap)
+    }
+    /// Get the on-screen rectangle of the subplot showing the given data set, e.g. to draw annotations.
+    /// This uses the layout of the most recent call of 'ui'. Returns None if the data set is hidden or nothing was shown yet
+    pub fn subplot_rect(&self, key: &Key, state: &ShowState<Key>) -> Option<egui::Rect> {
+        let rect = self.last_image_rect?;
+        let [width, height] = self.current_size;
+        let [left, top, w, h] =
+            self.showmap
+                .subplot_rect(key, [width as usize, height as usize], &state.multimap)?;
+        let scale = egui::vec2(rect.width() / width, rect.height() / height);
+        Some(egui::Rect::from_min_size(
+            rect.min + egui::vec2(left as f32, top as f32) * scale,
+            egui::vec2(w as f32, h as f32) * scale,
+        ))
+    }
+    /// Replace the data set with the given key, e.g. to show a new frame.
+    /// The shown area and the selection are kept. Returns false if there is no such data set
+    pub fn replace_data(&mut self, key: &Key, data: Data<Color>) -> bool {
+        let replaced = self.showmap.replace_data(key, data);
+        if replaced {
+            self.source_values.retain(|values| &values.key != key);
+            self.needs_rendering = true;
         }
+        replaced
+    }
+    /// Show or hide the colorbar. The currently shown area is kept
+    pub fn set_colorbar_visible(&mut self, visible: bool) {
+        if self.showmap.colorbar_visible() != visible {
+            self.showmap.set_colorbar_visible(visible);
+            self.needs_rendering = true;
+        }
+    }
+    /// Check if the colorbar is shown (if there is any)
+    pub fn colorbar_visible(&self) -> bool {
+        self.showmap.colorbar_visible()
+    }
+    /// Replace the colorbar (gradient, thickness, limits). Use 'None' to remove it
+    pub fn set_colorbar(
+        &mut self,
+        colorbar: Option<(crate::colors::Gradient<Color>, usize, (f64, f64))>,
+    ) {
+        self.showmap.set_colorbar(colorbar);
+        self.needs_rendering = true;
+    }
+    /// Change the value range shown by the colorbar, e.g. after updating the data.
+    /// Returns false (and changes nothing) if there is no colorbar or if lower < upper does not hold
+    pub fn set_colorbar_limits(&mut self, lower: f64, upper: f64) -> bool {
+        let changed = self.showmap.set_colorbar_limits(lower, upper);
+        if changed {
+            self.needs_rendering = true;
+        }
+        changed
+    }
+    /// Get the pixels of the most recent successful render, row by row.
+    /// This is kept until the next render completes. In case of a render problem, this is None
+    pub fn last_rendered(&self) -> Option<&[Color]> {
+        self.last_rendered.as_ref().map(|x| x.pixels.as_slice())
+    }
+    /// Get the size ([width, height]) of the most recent successful render
+    pub fn last_rendered_size(&self) -> Option<[usize; 2]> {
+        self.last_rendered.as_ref().map(|x| x.size)
+    }
+    /// Render at the given size ([width, height] in pixels) without uploading a texture, e.g. to show the heatmap in an own 'egui::Image'.
+    /// No egui context is needed, e.g. for server-side image generation or comparing against reference images.
+    /// This is the image shown by 'ui', but without what 'ui' paints on top of it: the extrema markers, the colorbar marker of the hovered value and the letterbox padding.
+    /// The widget itself is not affected, and the render problem of the state is not changed
+    pub fn render_color_image(
+        &self,
+        size: [usize; 2],
+        state: &mut ShowState<Key>,
+    ) -> Result<egui::ColorImage, RenderProblem> {
+        let [width, height] = size;
+        self.showmap
+            .render(width, height, &mut state.multimap)
+            .map(|pixels| egui::ColorImage { size, pixels })
     }
 
     fn convert_window2multimap(
@@ -316,37 +1115,165 @@ impl<Key: std::hash::Hash + Clone + Eq + Debug> MultiBitmapWidget<Key> {
             MultiMapPosition::NotHovering
         }
     }
-    /// Show widget
-    pub fn ui(&mut self, ui: &mut egui::Ui, state: &mut ShowState<Key>) {
-        let shown_before = state.currently_showing();
+    /// Screen position of the center of the given point of a data set, if it is shown
+    fn point_position(
+        &self,
+        key: &Key,
+        point: &CoordinatePoint,
+        state: &ShowState<Key>,
+    ) -> Option<egui::Pos2> {
+        let subplot = self.subplot_rect(key, state)?;
+        let shown = state.currently_showing()?;
+        if !shown.contains(point) {
+            return None;
+        }
+        let fraction =
+            |p: i32, start: i32, end: i32| ((p - start) as f32 + 0.5) / (end - start) as f32;
+        Some(
+            subplot.min
+                + egui::vec2(
+                    fraction(point.x, shown.left_top.x, shown.right_bottom.x) * subplot.width(),
+                    fraction(point.y, shown.left_top.y, shown.right_bottom.y) * subplot.height(),
+                ),
+        )
+    }
+    fn draw_extrema_markers(
+        &self,
+        painter: &egui::Painter,
+        markers: &ExtremaMarkers,
+        state: &ShowState<Key>,
+    ) {
+        let half = markers.size / 2.;
+        for values in &self.source_values {
+            let (min, max) = match values.extrema {
+                Some(extrema) => extrema,
+                None => continue,
+            };
+            for (point, color, diagonal) in [
+                (min, markers.min_color, true),
+                (max, markers.max_color, false),
+            ] {
+                let point = CoordinatePoint {
+                    x: point.x,
+                    y: point.y,
+                };
+                let center = match self.point_position(&values.key, &point, state) {
+                    Some(center) => center,
+                    None => continue,
+                };
+                let stroke = egui::Stroke::new(2., color);
+                let (a, b) = if diagonal {
+                    (egui::vec2(half, half), egui::vec2(half, -half))
+                } else {
+                    (egui::vec2(half, 0.), egui::vec2(0., half))
+                };
+                painter.line_segment([center - a, center + a], stroke);
+                painter.line_segment([center - b, center + b], stroke);
+            }
+        }
+    }
+    fn draw_colorbar_marker(
+        &self,
+        painter: &egui::Painter,
+        rect: egui::Rect,
+        size: [f32; 2],
+        value: f64,
+        color: Color,
+    ) {
+        if let Some(line) = self
+            .showmap
+            .colorbar_position(value, [size[0] as usize, size[1] as usize])
+        {
+            let scale = egui::vec2(rect.width() / size[0], rect.height() / size[1]);
+            let inner = rect.shrink(1.);
+            let [start, end] = line.map(|[column, row]| {
+                egui::pos2(
+                    (rect.left() + column * scale.x).clamp(inner.left(), inner.right()),
+                    (rect.top() + row * scale.y).clamp(inner.top(), inner.bottom()),
+                )
+            });
+            painter.line_segment([start, end], egui::Stroke::new(2., color));
+        }
+    }
+    /// Show widget. Returns the response of the underlying image
+    pub fn ui(&mut self, ui: &mut egui::Ui, state: &mut ShowState<Key>) -> egui::Response {
+        let shown_before = state.multimap.shown_rectangle.clone();
+        self.poll_clipboard(ui.ctx(), state);
         if let Some((before, size)) = self.copy_to_clipboard_delay {
             let now = std::time::Instant::now();
             if now - before > COPY_CLIPBOARD_DELAY {
                 self.copy_to_clipboard_delay = None;
-                self.copy_to_clipboard(size, state);
+                if let Err(problem) = self.copy_to_clipboard(size, state) {
+                    state.render_problem = Some(problem);
+                }
             }
         }
-        let size = self.update_size(ui.available_size());
-        self.render(state);
-        let rendered = self.rendered_image.texture_id(ui.ctx());
-        let image = egui::Widget::ui(
-            egui::Image::new(rendered, size).sense(egui::Sense::click_and_drag()),
-            ui,
-        );
+        if std::mem::take(&mut state.needs_rendering) {
+            self.needs_rendering = true;
+        }
+        if std::mem::take(&mut state.shown_set) {
+            self.animation = None;
+        }
+        if std::mem::take(&mut state.home_requested) {
+            let before = state.multimap.shown_rectangle.clone();
+            self.showmap.home(state.get_inner_mut());
+            self.start_animation(ui.ctx(), before, state);
+        }
+        self.advance_animation(ui.ctx(), state);
+        let available_size = ui.available_size();
+        let size = self.update_size(available_size, state);
+        let rendered = self.render(ui.ctx(), state);
+        let image = egui::Image::new(rendered, size).sense(egui::Sense::click_and_drag());
+        let image = match self.letterbox_color {
+            Some(color) if self.keep_aspect_ratio && self.dynamic_resizing => {
+                // the padding is not part of the image, so hovering it is 'NotHovering'
+                let (outer, _) = ui.allocate_exact_size(available_size, egui::Sense::hover());
+                ui.painter().rect_filled(outer, 0., color);
+                ui.put(
+                    egui::Rect::from_center_size(outer.center(), size.into()),
+                    image,
+                )
+            }
+            _ => egui::Widget::ui(image, ui),
+        };
 
         let mouse = image.hover_pos();
         let rect = image.rect;
+        self.last_image_rect = Some(rect);
         state.mouse = self.convert_window2bitmap(rect, mouse, size, &state.multimap);
+        if let Some(color) = self.colorbar_marker {
+            let value = match &state.mouse {
+                MultiMapPosition::Colorbar(value) => Some(*value),
+                MultiMapPosition::Pixel(key, point) => self
+                    .value_lookup
+                    .as_ref()
+                    .and_then(|lookup| lookup(key, point))
+                    .or_else(|| self.value_at(key, point).map(f64::from)),
+                MultiMapPosition::NotHovering | MultiMapPosition::NoData(_, _) => None,
+            };
+            if let Some(value) = value {
+                self.draw_colorbar_marker(ui.painter(), rect, size, value, color);
+            }
+        }
+        if let Some(markers) = &self.extrema_markers {
+            self.draw_extrema_markers(ui.painter(), markers, state);
+        }
         let mouse_pos = state.mouse.get_pos().cloned();
 
         let image = image.context_menu(|ui| {
             ui.vertical(|ui| {
-                if ui.button(&state.localization.text_home).clicked() {
+                if (self.navigation.zoom_enabled || self.navigation.pan_enabled)
+                    && ui.button(&state.localization.text_home).clicked()
+                {
+                    let before = state.multimap.shown_rectangle.clone();
                     self.showmap.home(state.get_inner_mut());
+                    self.start_animation(ui.ctx(), before, state);
                     self.needs_rendering = true;
                     ui.close_menu();
                 }
-                if ui.button(&state.localization.text_unselect_all).clicked() {
+                if self.navigation.selection_enabled
+                    && ui.button(&state.localization.text_unselect_all).clicked()
+                {
                     if state.unselect_all() {
                         self.needs_rendering = true;
                     }
@@ -359,7 +1286,7 @@ impl<Key: std::hash::Hash + Clone + Eq + Debug> MultiBitmapWidget<Key> {
                     ui.close_menu()
                 }
                 if let Some(key) = state.mouse.get_key() {
-                    if state.can_hide() {
+                    if state.can_hide(key) {
                         self.hide_key = Some(key.clone());
                     }
                 }
@@ -375,7 +1302,9 @@ impl<Key: std::hash::Hash + Clone + Eq + Debug> MultiBitmapWidget<Key> {
                     .button(&state.localization.text_copy_to_clipboard_instantly)
                     .clicked()
                 {
-                    self.copy_to_clipboard(size, state);
+                    if let Err(problem) = self.copy_to_clipboard(size, state) {
+                        state.render_problem = Some(problem);
+                    }
                     ui.close_menu()
                 }
                 if ui
@@ -390,34 +1319,63 @@ impl<Key: std::hash::Hash + Clone + Eq + Debug> MultiBitmapWidget<Key> {
         });
 
         state.clicked = false;
+        state.double_clicked = false;
 
         if image.double_clicked() {
-            if let Some(pos) = &mouse_pos {
-                self.showmap.center_to(pos, state.change_rect());
-                self.needs_rendering = true;
+            state.double_clicked = true;
+            if let Some(callback) = &mut self.double_click_callback {
+                callback(&state.mouse);
+            }
+            if self.double_click_recenters && self.navigation.pan_enabled {
+                if let Some(pos) = &mouse_pos {
+                    let before = state.multimap.shown_rectangle.clone();
+                    self.showmap.center_to(pos, state.change_rect());
+                    if self.recenter_snaps_to_data {
+                        self.showmap.snap_to_data(state.get_inner_mut());
+                    }
+                    self.start_animation(ui.ctx(), before, state);
+                    self.needs_rendering = true;
+                }
             }
         } else if image.clicked() {
             if let Some(pos) = &mouse_pos {
                 state.clicked = true;
-                self.showmap.select(
-                    pos,
-                    ui.ctx().input(|x| x.modifiers.ctrl),
-                    state.change_selected(),
-                );
-                self.needs_rendering = true;
+                if self.navigation.selection_enabled {
+                    let modifiers = ui.ctx().input(|x| x.modifiers);
+                    if modifiers.shift {
+                        self.select_row(pos.y, modifiers.ctrl, state);
+                    } else if modifiers.alt {
+                        self.select_column(pos.x, modifiers.ctrl, state);
+                    } else if let Some(selected) = self.showmap.select(
+                        pos,
+                        modifiers.ctrl,
+                        &state.multimap.selected,
+                        self.max_selected,
+                    ) {
+                        state.change_selected(selected);
+                    } else {
+                        state.events.push(Event::SelectionLimitReached);
+                    }
+                    self.needs_rendering = true;
+                }
             }
         }
-        if image.drag_started() {
+        if !self.navigation.drag_zoom_enabled {
+            // dragging is ignored
+        } else if image.drag_started() {
             if let Some(pos) = &mouse_pos {
+                self.animation = None;
                 self.showmap.drag_start(pos);
                 self.needs_rendering = true;
             }
         } else if image.drag_released() {
+            let before = state.multimap.shown_rectangle.clone();
             if let Some(pos) = &mouse_pos {
                 self.showmap.drag_release(Some(pos), state.change_rect());
             } else {
                 self.showmap.drag_release(None, state.change_rect());
             }
+            self.start_animation(ui.ctx(), before, state);
             self.needs_rendering = true;
         } else if image.dragged() {
             if let Some(pos) = &mouse_pos {
@@ -427,8 +1385,22 @@ impl<Key: std::hash::Hash + Clone + Eq + Debug> MultiBitmapWidget<Key> {
             }
         }
 
+        // copy shortcut
+        if let Some(shortcut) = &self.copy_shortcut {
+            if image.hovered()
+                && ui.ctx().memory(|x| x.focus().is_none())
+                && ui.ctx().input_mut(|x| x.consume_shortcut(shortcut))
+            {
+                if let Err(problem) = self.copy_to_clipboard(size, state) {
+                    state.render_problem = Some(problem);
+                }
+            }
+        }
         // keyboard movement and zoom and homeing
-        if image.hovered() && ui.ctx().memory(|x| x.focus().is_none()) {
+        if self.navigation.keyboard_enabled
+            && image.hovered()
+            && ui.ctx().memory(|x| x.focus().is_none())
+        {
             if let Some((key, modifiers)) = ui.ctx().input(|x| {
                 let keys = &x.keys_down;
                 if keys.len() == 1 {
@@ -444,7 +1416,8 @@ impl<Key: std::hash::Hash + Clone + Eq + Debug> MultiBitmapWidget<Key> {
                     (egui::Key::ArrowRight, KeyBoardDirection::Right),
                     (egui::Key::ArrowLeft, KeyBoardDirection::Left),
                 ] {
-                    if key == needed_key && modifiers.is_none() {
+                    if self.navigation.pan_enabled && key == needed_key && modifiers.is_none() {
+                        self.animation = None;
                         self.showmap
                             .translate_keyboard(direction, state.change_rect());
                         self.needs_rendering = true;
@@ -455,58 +1428,167 @@ impl<Key: std::hash::Hash + Clone + Eq + Debug> MultiBitmapWidget<Key> {
                 for (needed_key, zoom_increment) in
                     [(egui::Key::PlusEquals, 1), (egui::Key::Minus, -1)]
                 {
-                    if key == needed_key && modifiers.is_none() {
-                        self.showmap.zoom(zoom_increment, state.change_rect());
+                    if self.navigation.zoom_enabled && key == needed_key && modifiers.is_none() {
+                        self.animation = None;
+                        self.showmap
+                            .zoom([zoom_increment, zoom_increment], state.change_rect());
                         self.needs_rendering = true;
                         break;
                     }
                 }
-                if modifiers.is_none() && key == egui::Key::Home {
+                if (self.navigation.zoom_enabled || self.navigation.pan_enabled)
+                    && modifiers.is_none()
+                    && key == egui::Key::Home
+                {
+                    let before = state.multimap.shown_rectangle.clone();
                     self.showmap.home(state.get_inner_mut());
+                    self.start_animation(ui.ctx(), before, state);
                     self.needs_rendering = true;
                 }
             };
         }
         // mouse scroll
         if image.hovered() {
-            let (scroll_delta, modifiers) = ui.ctx().input(|x| (x.scroll_delta, x.modifiers));
-            let scroll_delta = if modifiers.shift {
-                scroll_delta.x * 5. //TODO: make this magnifier configurable
-            } else {
-                scroll_delta.y
-            };
-            let scroll_delta = (scroll_delta / 50.).round() as i32; // TODO: Does this 50 depend on my machine/mouse/...
-            if scroll_delta != 0 {
-                if let Some(before) = self
-                    .convert_window2bitmap(rect, mouse, size, &state.multimap)
-                    .get_pos()
-                {
-                    self.showmap.zoom(scroll_delta, state.change_rect());
+            let (scroll_delta, zoom_delta, modifiers) = ui
+                .ctx()
+                .input(|x| (x.scroll_delta, x.zoom_delta(), x.modifiers));
+            let sensitivity = self.scroll_zoom_sensitivity.max(1.);
+            if modifiers.shift && self.shift_scroll_pans {
+                // like zoom steps, small deltas (e.g. from trackpads) are accumulated during the gesture
+                let points = scroll_delta.x * self.horizontal_scroll_factor / sensitivity;
+                let pan = if self.navigation.pan_enabled && points != 0. {
+                    state.multimap.accumulate_pan(points)
+                } else {
+                    state.multimap.reset_scroll();
+                    0
+                };
+                if pan != 0 {
+                    self.animation = None;
+                    self.showmap
+                        .translate(CoordinatePoint { x: -pan, y: 0 }, state.change_rect());
                     self.needs_rendering = true;
-                    if let Some(after) = self
+                }
+            } else {
+                // ctrl+scroll zooms the x axis only, alt+scroll the y axis only.
+                // ctrl+scroll (and pinch gestures) are reported by egui as zoom instead of scroll
+                let (steps, [x_axis, y_axis]) = if zoom_delta != 1. {
+                    let axes = if modifiers.command { [1, 0] } else { [1, 1] };
+                    (zoom_delta.ln() * ZOOM_GESTURE_POINTS / sensitivity, axes)
+                } else if modifiers.shift {
+                    (
+                        scroll_delta.x * self.horizontal_scroll_factor / sensitivity,
+                        [1, 1],
+                    )
+                } else if modifiers.alt {
+                    (scroll_delta.y / sensitivity, [0, 1])
+                } else {
+                    (scroll_delta.y / sensitivity, [1, 1])
+                };
+                // trackpads emit many small deltas, so fractional steps are accumulated
+                // until the gesture ends, i.e. until a frame without scrolling
+                let steps = if self.navigation.zoom_enabled && steps != 0. {
+                    state.multimap.accumulate_scroll(steps)
+                } else {
+                    state.multimap.reset_scroll();
+                    0
+                };
+                if steps != 0 {
+                    if let Some(before) = self
                         .convert_window2bitmap(rect, mouse, size, &state.multimap)
                         .get_pos()
                     {
-                        self.showmap.translate(
-                            CoordinatePoint {
-                                x: before.x - after.x,
-                                y: before.y - after.y,
-                            },
-                            state.change_rect(),
-                        )
+                        self.animation = None;
+                        self.showmap
+                            .zoom([steps * x_axis, steps * y_axis], state.change_rect());
+                        self.needs_rendering = true;
+                        if let Some(after) = self
+                            .convert_window2bitmap(rect, mouse, size, &state.multimap)
+                            .get_pos()
+                        {
+                            self.showmap.translate(
+                                CoordinatePoint {
+                                    x: before.x - after.x,
+                                    y: before.y - after.y,
+                                },
+                                state.change_rect(),
+                            )
+                        }
                     }
                 }
             }
+        } else {
+            state.multimap.reset_scroll();
         }
-        // shown area changed
-        if state.currently_showing() != shown_before {
-            state.events.push(Event::ShowRectangle);
+        // shown area changed, reported like by 'translate'
+        self.shown_changed(shown_before, state);
+        if self.clip_selection_to_view {
+            // during an animation, the selection is clipped to its target
+            let shown = self
+                .animation
+                .as_ref()
+                .map(|animation| CoordinateRect::from(&animation.to))
+                .or_else(|| state.currently_showing());
+            if let Some(shown) = shown {
+                state.clip_selection_to(&shown);
+            }
+        }
+        image
+    }
+
+    fn start_animation(
+        &mut self,
+        ctx: &egui::Context,
+        before: Option<crate::multimap::ShowRect>,
+        state: &mut ShowState<Key>,
+    ) {
+        self.animation = None;
+        if let (Some(duration), Some(from), Some(to)) = (
+            self.animation_duration,
+            before,
+            state.multimap.shown_rectangle.clone(),
+        ) {
+            if from != to {
+                state.multimap.shown_rectangle = Some(from.clone());
+                self.animation = Some(ShowRectAnimation {
+                    from,
+                    to,
+                    start: std::time::Instant::now(),
+                    duration,
+                });
+                ctx.request_repaint();
+            }
+        }
+    }
+
+    fn advance_animation(&mut self, ctx: &egui::Context, state: &mut ShowState<Key>) {
+        if let Some(ShowRectAnimation {
+            from,
+            to,
+            start,
+            duration,
+        }) = &self.animation
+        {
+            let ratio = start.elapsed().as_secs_f32() / duration.as_secs_f32();
+            if ratio.is_finite() && ratio < 1. {
+                // smoothstep easing
+                let ratio = ratio * ratio * (3. - 2. * ratio);
+                state.multimap.shown_rectangle = Some(from.interpolate(to, ratio));
+                ctx.request_repaint();
+            } else {
+                state.multimap.shown_rectangle = Some(to.clone());
+                self.animation = None;
+            }
+            self.needs_rendering = true;
         }
     }
 
-    fn update_size(&mut self, available_size: egui::Vec2) -> [f32; 2] {
+    fn update_size(&mut self, available_size: egui::Vec2, state: &ShowState<Key>) -> [f32; 2] {
         if self.dynamic_resizing {
-            let new_size = [available_size.x, available_size.y];
+            let new_size = if self.keep_aspect_ratio {
+                self.aspect_size(available_size, state)
+            } else {
+                [available_size.x, available_size.y]
+            };
             if self.current_size != new_size {
                 self.current_size = new_size;
                 self.needs_rendering = true;
@@ -516,79 +1598,131 @@ impl<Key: std::hash::Hash + Clone + Eq + Debug> MultiBitmapWidget<Key> {
             self.current_size
         }
     }
+    /// Largest size fitting into the available size, which does not stretch the data
+    fn aspect_size(&self, available_size: egui::Vec2, state: &ShowState<Key>) -> [f32; 2] {
+        let width = available_size.x;
+        let height = match self
+            .showmap
+            .height_for_width(width as usize, &state.multimap)
+        {
+            Some(height) => height as f32,
+            None => return [available_size.x, available_size.y],
+        };
+        if height > available_size.y && height > 0. {
+            // the height is roughly proportional to the width
+            let width = (width * available_size.y / height).floor();
+            let height = self
+                .showmap
+                .height_for_width(width as usize, &state.multimap)
+                .map_or(available_size.y, |height| height as f32);
+            [width, height.min(available_size.y)]
+        } else {
+            [width, height]
+        }
+    }
 
-    fn render(&mut self, state: &mut ShowState<Key>) {
-        if self.needs_rendering {
+    /// Render the whole widget into one buffer and upload it as a single texture.
+    /// Keep this invariant when adding per-subplot features: one upload per subplot is much slower for many subplots,
+    /// see the example 'upload_benchmark'
+    fn render(&mut self, ctx: &egui::Context, state: &mut ShowState<Key>) -> egui::TextureId {
+        // a render problem is retried when the size changes (see 'update_size'), so that e.g. enlarging the widget recovers
+        if self.needs_rendering || self.rendered_image.is_none() {
             self.needs_rendering = false;
             let w = self.current_size[0] as usize;
             let h = self.current_size[1] as usize;
-            let (image, problem) = match self.showmap.render(w, h, &mut state.multimap) {
-                Ok(image) => (
-                    egui::ColorImage {
+            let (image, problem) = match self.render_color_image([w, h], state) {
+                Ok(image) => {
+                    self.last_rendered = Some(image.clone());
+                    (image, None)
+                }
+                Err(RenderProblem::NoData) => {
+                    // an empty widget is not an error for the user, so no placeholder is shown
+                    self.last_rendered = None;
+                    let image = egui::ColorImage {
                         size: [w, h],
-                        pixels: image,
-                    },
-                    None,
-                ),
-                Err(err) => (egui::ColorImage::new([w, h], Color::GOLD), Some(err)),
+                        pixels: self
+                            .showmap
+                            .render_empty(w, h, &state.localization.text_no_data),
+                    };
+                    (image, Some(RenderProblem::NoData))
+                }
+                Err(err) => {
+                    self.last_rendered = None;
+                    (
+                        egui::ColorImage::new([w, h], self.placeholder_color),
+                        Some(err),
+                    )
+                }
             };
             state.render_problem = problem;
-            self.rendered_image = RenderedImage::from_color_image(self.debug_name.clone(), image);
+            let options = self.texture_options;
+            if let Some(texture) = &mut self.rendered_image {
+                texture.set(image, options);
+            } else {
+                self.rendered_image = Some(ctx.load_texture(&self.debug_name, image, options));
+            }
         }
+        self.rendered_image
+            .as_ref()
+            .map(|texture| texture.id())
+            .unwrap_or_default()
     }
 
-    fn copy_to_clipboard(&self, size: [f32; 2], state: &mut ShowState<Key>) {
-        let width = size[0] as usize;
-        let height = size[1] as usize;
-        match self.showmap.render(width, height, &mut state.multimap) {
-            Ok(data) => {
-                #[cfg(target_os = "windows")]
-                {
-                    if let Ok(_clip) = clipboard_win::Clipboard::new_attempts(10) {
-                        if let Some(fmt) = clipboard_win::register_format("PNG") {
-                            let image = image::ImageBuffer::from_fn(
-                                size[0] as u32,
-                                size[1] as u32,
-                                |x, y| {
-                                    let c = data[(size[0] as u32 * y + x) as usize];
-                                    let (r, g, b, _a) = c.to_tuple();
-                                    image::Rgb([r, g, b])
-                                },
-                            );
-
-                            let mut writer = std::io::Cursor::new(Vec::new());
-                            if let Err(e) =
-                                image.write_to(&mut writer, image::ImageOutputFormat::Png)
-                            {
-                                panic!("Failed to convert to png: {e}")
-                            };
-                            let image = writer.into_inner();
-                            if let Err(e) = clipboard_win::raw::set(fmt.into(), &image) {
-                                panic!("Failed to copy to clipboard: {e}");
-                            }
-                        }
-                    }
+    fn finish_clipboard(result: Result<(), RenderProblem>, state: &mut ShowState<Key>) {
+        match result {
+            Ok(()) => state.events.push(Event::CopiedToClipboard),
+            Err(problem) => state.render_problem = Some(problem),
+        }
+    }
+
+    fn poll_clipboard(&mut self, ctx: &egui::Context, state: &mut ShowState<Key>) {
+        if let Some(receiver) = &self.clipboard_pending {
+            match receiver.try_recv() {
+                Ok(result) => {
+                    self.clipboard_pending = None;
+                    Self::finish_clipboard(result, state);
                 }
-                #[cfg(target_os = "linux")]
-                {
-                    let bytes = data
-                        .into_iter()
-                        .flat_map(|x| x.to_array())
-                        .collect::<Vec<_>>();
-                    let mut clipboard = arboard::Clipboard::new().unwrap();
-                    let r = clipboard.set_image(arboard::ImageData {
-                        width,
-                        height,
-                        bytes: bytes.into(),
-                    });
-                    if let Err(e) = r {
-                        state.render_problem =
-                            Some(RenderProblem::ClipboardIssue(format!("{e:?}")));
-                    }
+                Err(std::sync::mpsc::TryRecvError::Empty) => {
+                    ctx.request_repaint_after(CLIPBOARD_POLL_INTERVAL);
+                }
+                Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                    self.clipboard_pending = None;
+                    state.render_problem = Some(RenderProblem::ClipboardIssue(
+                        "Clipboard thread stopped unexpectedly".to_string(),
+                    ));
                 }
             }
-            Err(_) => todo!(),
         }
+    }
+
+    /// Copy the image to the clipboard. Emits 'Event::CopiedToClipboard' once done.
+    /// If copying is done in the background, a failure is reported via the render problem of the state later
+    fn copy_to_clipboard(
+        &mut self,
+        size: [f32; 2],
+        state: &mut ShowState<Key>,
+    ) -> Result<(), RenderProblem> {
+        let width = size[0] as usize;
+        let height = size[1] as usize;
+        let data = self
+            .showmap
+            .render(width, height, &mut state.multimap)
+            .map_err(|e| {
+                RenderProblem::ClipboardIssue(format!("Failed to render the image: {e:?}"))
+            })?;
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.clipboard_in_background {
+            let (sender, receiver) = std::sync::mpsc::channel();
+            std::thread::spawn(move || {
+                // the receiver may be gone already, e.g. if the widget was dropped
+                let _ = sender.send(write_to_clipboard(data, width, height));
+            });
+            self.clipboard_pending = Some(receiver);
+            return Ok(());
+        }
+        write_to_clipboard(data, width, height)?;
+        state.events.push(Event::CopiedToClipboard);
+        Ok(())
         /*
             fn render_to_buffer(&mut self, size: [f32; 2]) -> Option<Vec<u8>> {
             if let Ok(image) = self.showmap.render(size[0] as usize, size[1] as usize) {
@@ -609,3 +1743,960 @@ impl<Key: std::hash::Hash + Clone + Eq + Debug> MultiBitmapWidget<Key> {
         } */
     }
 }
+
+fn write_to_clipboard(data: Vec<Color>, width: usize, height: usize) -> Result<(), RenderProblem> {
+    #[cfg(target_os = "windows")]
+    {
+        let _clip = clipboard_win::Clipboard::new_attempts(10)
+            .map_err(|e| RenderProblem::ClipboardIssue(format!("Failed to open clipboard: {e}")))?;
+        let fmt = clipboard_win::register_format("PNG").ok_or_else(|| {
+            RenderProblem::ClipboardIssue("Failed to register PNG clipboard format".to_string())
+        })?;
+        let image = crate::bitmap_data::encode_png(&data, width, height)
+            .map_err(|e| RenderProblem::ClipboardIssue(format!("Failed to convert to png: {e}")))?;
+        clipboard_win::raw::set(fmt.into(), &image).map_err(|e| {
+            RenderProblem::ClipboardIssue(format!("Failed to copy to clipboard: {e}"))
+        })?;
+    }
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    {
+        let bytes = data
+            .into_iter()
+            .flat_map(|x| x.to_array())
+            .collect::<Vec<_>>();
+        let mut clipboard = arboard::Clipboard::new().map_err(|e| {
+            RenderProblem::ClipboardIssue(format!("Failed to open clipboard: {e:?}"))
+        })?;
+        clipboard
+            .set_image(arboard::ImageData {
+                width,
+                height,
+                bytes: bytes.into(),
+            })
+            .map_err(|e| RenderProblem::ClipboardIssue(format!("{e:?}")))?;
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+    let _ = (data, width, height);
+    if cfg!(any(
+        target_os = "windows",
+        target_os = "linux",
+        target_os = "macos"
+    )) {
+        Ok(())
+    } else {
+        Err(RenderProblem::ClipboardIssue(
+            "clipboard not supported on this platform".into(),
+        ))
+    }
+}
+
+#[cfg(test)]
+fn test_settings() -> MultiBitmapWidgetSettings {
+    MultiBitmapWidgetSettings {
+        start_size: None,
+        keep_aspect_ratio: false,
+        letterbox_color: None,
+        id: "test".to_owned(),
+        texture_options: egui::TextureOptions::default(),
+        placeholder_color: Color::DARK_GRAY,
+        boundary_between_data: ColorWithThickness {
+            color: Color::DARK_GRAY,
+            thickness: 2,
+        },
+        colorbar: Some((
+            crate::colors::Gradient::with_options(&crate::colors::ColorGradientOptions::StartEnd {
+                start: Color::RED,
+                end: Color::BLUE,
+                steps: 8,
+            }),
+            20,
+            (0., 1.),
+        )),
+        background: Color::BLACK,
+        missing_data_color: None,
+        boundary_unselected: ColorWithThickness {
+            color: Color::GRAY,
+            thickness: 1,
+        },
+        boundary_selected: Color::WHITE,
+        boundary_factor_min: 3,
+        downsampling: Downsampling::Nearest,
+        scale_bar: None,
+        grid: None,
+        truncate_titles: false,
+        colorbar_labels: ColorbarLabels::Auto,
+        colorbar_relative_thickness: None,
+        colorbar_gap: None,
+        drag_dim_factor: 0.5,
+        drag_outline: None,
+        selection_style: SelectionStyle::Outline,
+        colorbar_font: None,
+        min_font_height: 8.,
+        text_compositing: TextCompositing::Additive,
+        legend: None,
+        colorbar_ticks: 5,
+        colorbar_placement: ColorbarPlacement::Right,
+        colorbar_formatter: None,
+        min_shown_extent: None,
+        max_shown_extent: None,
+        pan_bounds: None,
+        upsampling: Upsampling::Nearest,
+        colorbar_scale: crate::ScaleMode::Linear,
+        navigation: NavigationConfig::default(),
+        double_click_recenters: true,
+        recenter_snaps_to_data: false,
+        animation_duration: None,
+        horizontal_scroll_factor: 5.,
+        scroll_zoom_sensitivity: 50.,
+        shift_scroll_pans: false,
+        max_selected: None,
+        clip_selection_to_view: false,
+        colorbar_marker: None,
+        extrema_markers: None,
+        clipboard_in_background: false,
+        copy_shortcut: None,
+    }
+}
+
+/// Run a single frame showing the widget, using all of the given screen size
+#[cfg(test)]
+fn run_frame<Key: std::hash::Hash + Clone + Eq + Debug>(
+    ctx: &egui::Context,
+    widget: &mut MultiBitmapWidget<Key>,
+    state: &mut ShowState<Key>,
+    [width, height]: [f32; 2],
+) {
+    let input = egui::RawInput {
+        screen_rect: Some(egui::Rect::from_min_size(
+            egui::Pos2::ZERO,
+            egui::vec2(width, height),
+        )),
+        ..Default::default()
+    };
+    let _ = ctx.run(input, |ctx| {
+        egui::CentralPanel::default()
+            .frame(egui::Frame::none())
+            .show(ctx, |ui| widget.ui(ui, state));
+    });
+}
+
+#[test]
+fn render_problem_recovers_test() {
+    let ctx = egui::Context::default();
+    let mut widget = MultiBitmapWidget::with_settings(
+        vec![(0, Data::example(4, 4, CoordinatePoint { x: 0, y: 0 }))],
+        test_settings(),
+    );
+    let mut state = widget.default_state_english();
+    // narrower than the colorbar
+    run_frame(&ctx, &mut widget, &mut state, [10., 10.]);
+    assert!(matches!(
+        state.render_problem(),
+        Some(RenderProblem::WidthSmallerThanColorBar)
+    ));
+    assert!(widget.last_rendered().is_none());
+    // the same size is not rendered again
+    state.clear_render_problem();
+    run_frame(&ctx, &mut widget, &mut state, [10., 10.]);
+    assert!(state.render_problem().is_none());
+    run_frame(&ctx, &mut widget, &mut state, [60., 40.]);
+    assert!(state.render_problem().is_none());
+    assert_eq!(widget.last_rendered_size(), Some([60, 40]));
+}
+
+#[test]
+fn empty_widget_test() {
+    let ctx = egui::Context::default();
+    let mut widget = MultiBitmapWidget::<usize>::with_settings(Vec::new(), test_settings());
+    let mut state = widget.default_state_english();
+    run_frame(&ctx, &mut widget, &mut state, [60., 40.]);
+    assert!(matches!(
+        state.render_problem(),
+        Some(RenderProblem::NoData)
+    ));
+    // a background-colored image is shown instead of the error placeholder
+    assert_eq!(
+        widget.rendered_image.as_ref().map(|x| x.size()),
+        Some([60, 40])
+    );
+}
+
+#[test]
+fn clip_selection_to_view_test() {
+    let ctx = egui::Context::default();
+    let mut widget = MultiBitmapWidget::with_settings(
+        vec![(0, Data::example(4, 4, CoordinatePoint { x: 0, y: 0 }))],
+        MultiBitmapWidgetSettings {
+            clip_selection_to_view: true,
+            ..test_settings()
+        },
+    );
+    let mut state = widget.default_state_english();
+    run_frame(&ctx, &mut widget, &mut state, [60., 40.]);
+    let inside = CoordinatePoint { x: 1, y: 1 };
+    let outside = CoordinatePoint { x: 100, y: 1 };
+    state.make_selected([inside.clone(), outside].into_iter().collect());
+    state.events();
+    run_frame(&ctx, &mut widget, &mut state, [60., 40.]);
+    assert_eq!(state.selected_sorted(), vec![inside]);
+    assert!(state.events().iter().any(|e| matches!(e, Event::Selection)));
+    // clipping is not an undo step, so undo gets past it
+    assert!(state.undo_selection());
+    run_frame(&ctx, &mut widget, &mut state, [60., 40.]);
+    assert!(state.selected().is_empty());
+    assert!(!state.undo_selection());
+}
+
+#[test]
+fn zoom_to_selection_test() {
+    let ctx = egui::Context::default();
+    let mut widget = MultiBitmapWidget::with_settings(
+        vec![(0, Data::example(20, 20, CoordinatePoint { x: 0, y: 0 }))],
+        test_settings(),
+    );
+    let mut state = widget.default_state_english();
+    run_frame(&ctx, &mut widget, &mut state, [60., 40.]);
+    state.events();
+    let home = state.currently_showing();
+    assert!(!state.zoom_to_selection());
+    assert_eq!(state.currently_showing(), home);
+    assert!(state.events().is_empty());
+
+    let point = |x, y| CoordinatePoint { x, y };
+    let rect = |l, t, r, b| CoordinateRect {
+        left_top: point(l, t),
+        right_bottom: point(r, b),
+    };
+    state.make_selected([point(3, 4), point(12, 6), point(7, 15)].into());
+    assert!(state.zoom_to_selection());
+    assert_eq!(state.currently_showing(), Some(rect(2, 3, 14, 17)));
+    assert!(state
+        .events()
+        .iter()
+        .any(|e| matches!(e, Event::ShowRectangle)));
+    // a single point is expanded to the minimal extent
+    state.make_selected([point(10, 10)].into());
+    assert!(state.zoom_to_selection());
+    assert_eq!(state.currently_showing(), Some(rect(8, 8, 13, 13)));
+}
+
+#[test]
+fn pinned_test() {
+    let mut state = MultiBitmapWidget::with_settings(
+        vec![
+            (0, Data::example(4, 4, CoordinatePoint { x: 0, y: 0 })),
+            (1, Data::example(4, 4, CoordinatePoint { x: 0, y: 0 })),
+            (2, Data::example(4, 4, CoordinatePoint { x: 0, y: 0 })),
+        ],
+        test_settings(),
+    )
+    .default_state_english();
+    assert!(!state.set_pinned(&3, true));
+    assert!(state.set_pinned(&0, true));
+    assert!(!state.set_pinned(&0, true));
+    assert!(state.is_pinned(&0));
+    assert!(!state.can_hide(&0));
+    assert!(!state.set_visible(&0, false));
+    assert!(state.set_visible(&1, false));
+    assert!(state.set_visible(&2, false));
+    // the last shown dataset still cannot be hidden after unpinning
+    assert!(state.set_pinned(&0, false));
+    assert!(!state.set_visible(&0, false));
+    assert!(state.set_visible(&1, true));
+    assert!(state.set_visible(&0, false));
+    assert!(!state.is_visible(&0));
+}
+
+#[test]
+fn peek_events_test() {
+    let mut state = MultiBitmapWidget::with_settings(
+        vec![(0, Data::example(4, 4, CoordinatePoint { x: 0, y: 0 }))],
+        test_settings(),
+    )
+    .default_state_english();
+    assert!(state.peek_events().is_empty());
+    state.make_selected([CoordinatePoint { x: 1, y: 1 }].into());
+    state.undo_selection();
+    assert!(matches!(state.peek_events(), [Event::Selection]));
+    assert!(matches!(state.peek_events(), [Event::Selection]));
+    assert!(matches!(state.events().as_slice(), [Event::Selection]));
+    assert!(state.peek_events().is_empty());
+}
+
+#[test]
+fn set_shown_test() {
+    let ctx = egui::Context::default();
+    let mut widget = MultiBitmapWidget::with_settings(
+        vec![(0, Data::example(4, 4, CoordinatePoint { x: 0, y: 0 }))],
+        test_settings(),
+    );
+    let mut state = widget.default_state_english();
+    run_frame(&ctx, &mut widget, &mut state, [60., 40.]);
+    let rect = || CoordinateRect {
+        left_top: CoordinatePoint { x: 1, y: 2 },
+        right_bottom: CoordinatePoint { x: 3, y: 4 },
+    };
+    assert!(!state.set_shown(CoordinateRect {
+        left_top: CoordinatePoint { x: 1, y: 2 },
+        right_bottom: CoordinatePoint { x: 1, y: 4 },
+    }));
+    assert!(state.set_shown(rect()));
+    state.events();
+    run_frame(&ctx, &mut widget, &mut state, [60., 40.]);
+    assert_eq!(state.currently_showing(), Some(rect()));
+    assert!(!state
+        .events()
+        .iter()
+        .any(|e| matches!(e, Event::ShowRectangle)));
+    widget.home(&mut state);
+    assert_eq!(state.currently_showing(), widget.data_bounds(&state));
+}
+
+#[test]
+fn set_shown_cancels_animation_test() {
+    let ctx = egui::Context::default();
+    let mut widget = MultiBitmapWidget::with_settings(
+        vec![(0, Data::example(20, 20, CoordinatePoint { x: 0, y: 0 }))],
+        MultiBitmapWidgetSettings {
+            animation_duration: Some(std::time::Duration::from_secs(60)),
+            ..test_settings()
+        },
+    );
+    let mut state = widget.default_state_english();
+    run_frame(&ctx, &mut widget, &mut state, [60., 40.]);
+    let rect = || CoordinateRect {
+        left_top: CoordinatePoint { x: 1, y: 2 },
+        right_bottom: CoordinatePoint { x: 3, y: 4 },
+    };
+    let animate_home = |widget: &mut MultiBitmapWidget<usize>, state: &mut ShowState<usize>| {
+        assert!(state.set_shown(rect()));
+        run_frame(&ctx, widget, state, [60., 40.]);
+        state.request_home();
+        run_frame(&ctx, widget, state, [60., 40.]);
+        assert!(widget.animation.is_some());
+    };
+    // the rectangle set during the animation is kept
+    animate_home(&mut widget, &mut state);
+    assert!(state.set_shown(rect()));
+    run_frame(&ctx, &mut widget, &mut state, [60., 40.]);
+    assert_eq!(state.currently_showing(), Some(rect()));
+    // the same for zooming to the selection
+    animate_home(&mut widget, &mut state);
+    state.make_selected([CoordinatePoint { x: 10, y: 10 }].into_iter().collect());
+    assert!(state.zoom_to_selection());
+    let zoomed = state.currently_showing();
+    run_frame(&ctx, &mut widget, &mut state, [60., 40.]);
+    assert_eq!(state.currently_showing(), zoomed);
+}
+
+#[test]
+fn zoom_translate_center_to_test() {
+    let ctx = egui::Context::default();
+    let mut widget = MultiBitmapWidget::with_settings(
+        vec![(0, Data::example(20, 20, CoordinatePoint { x: 0, y: 0 }))],
+        test_settings(),
+    );
+    let mut state = widget.default_state_english();
+    // no view yet
+    widget.zoom(1, &mut state);
+    assert_eq!(state.currently_showing(), None);
+    run_frame(&ctx, &mut widget, &mut state, [60., 40.]);
+    let home = state.currently_showing().unwrap();
+    state.events();
+    widget.zoom(2, &mut state);
+    let zoomed = state.currently_showing().unwrap();
+    assert_eq!(
+        zoomed.left_top,
+        CoordinatePoint {
+            x: home.left_top.x + 2,
+            y: home.left_top.y + 2
+        }
+    );
+    widget.translate(CoordinatePoint { x: 3, y: -1 }, &mut state);
+    let moved = state.currently_showing().unwrap();
+    assert_eq!(
+        moved.left_top,
+        CoordinatePoint {
+            x: zoomed.left_top.x + 3,
+            y: zoomed.left_top.y - 1
+        }
+    );
+    widget.center_to(&CoordinatePoint { x: 10, y: 10 }, &mut state);
+    let centered = state.currently_showing().unwrap();
+    assert!(centered.left_top.x <= 10 && centered.right_bottom.x >= 10);
+    assert!(centered.left_top.y <= 10 && centered.right_bottom.y >= 10);
+    // each change is reported once
+    let changes = state
+        .events()
+        .iter()
+        .filter(|e| matches!(e, Event::ShowRectangle))
+        .count();
+    assert_eq!(changes, 3);
+    // moving by nothing is no change
+    widget.translate(CoordinatePoint { x: 0, y: 0 }, &mut state);
+    assert!(state.take_view_change().is_none());
+    // resetting the view is reported, too
+    widget.home(&mut state);
+    assert_eq!(state.take_view_change(), Some(home));
+    widget.home(&mut state);
+    assert!(state.take_view_change().is_none());
+}
+
+#[test]
+fn request_home_test() {
+    let ctx = egui::Context::default();
+    let mut widget = MultiBitmapWidget::with_settings(
+        vec![(0, Data::example(20, 20, CoordinatePoint { x: 0, y: 0 }))],
+        test_settings(),
+    );
+    let mut state = widget.default_state_english();
+    run_frame(&ctx, &mut widget, &mut state, [60., 40.]);
+    let home = state.currently_showing();
+    widget.zoom(3, &mut state);
+    run_frame(&ctx, &mut widget, &mut state, [60., 40.]);
+    assert_ne!(state.currently_showing(), home);
+    state.events();
+    state.request_home();
+    assert!(state.needs_repaint());
+    // only applied by the next frame
+    assert_ne!(state.currently_showing(), home);
+    run_frame(&ctx, &mut widget, &mut state, [60., 40.]);
+    assert_eq!(state.currently_showing(), home);
+    assert!(state
+        .events()
+        .iter()
+        .any(|e| matches!(e, Event::ShowRectangle)));
+    run_frame(&ctx, &mut widget, &mut state, [60., 40.]);
+    assert!(state.events().is_empty());
+}
+
+#[test]
+fn take_navigation_events_test() {
+    let widget = MultiBitmapWidget::<usize>::with_settings(Vec::new(), test_settings());
+    let mut state = widget.default_state_english();
+    state.events.push(Event::Selection);
+    state.events.push(Event::ShowRectangle);
+    let navigation = state.take_navigation_events();
+    assert!(matches!(navigation.as_slice(), [Event::ShowRectangle]));
+    assert!(state.take_navigation_events().is_empty());
+    assert!(matches!(state.events().as_slice(), [Event::Selection]));
+}
+
+#[test]
+fn scroll_zoom_sensitivity_test() {
+    // one scroll delta per frame, 0 for a frame without scrolling
+    let zooms_with = |scroll_zoom_sensitivity, deltas: &[f32], gesture: bool| {
+        let ctx = egui::Context::default();
+        let mut widget = MultiBitmapWidget::with_settings(
+            vec![(0, Data::example(40, 40, CoordinatePoint { x: 0, y: 0 }))],
+            MultiBitmapWidgetSettings {
+                colorbar: None,
+                scroll_zoom_sensitivity,
+                ..test_settings()
+            },
+        );
+        let mut state = widget.default_state_english();
+        let input = |events| egui::RawInput {
+            screen_rect: Some(egui::Rect::from_min_size(
+                egui::Pos2::ZERO,
+                egui::vec2(60., 60.),
+            )),
+            events,
+            ..Default::default()
+        };
+        let scrolls = deltas.iter().map(|delta| {
+            if *delta == 0. {
+                Vec::new()
+            } else if gesture {
+                vec![egui::Event::Zoom((*delta / ZOOM_GESTURE_POINTS).exp())]
+            } else {
+                vec![egui::Event::Scroll(egui::vec2(0., *delta))]
+            }
+        });
+        for events in
+            std::iter::once(vec![egui::Event::PointerMoved(egui::pos2(30., 30.))]).chain(scrolls)
+        {
+            let _ = ctx.run(input(events), |ctx| {
+                egui::CentralPanel::default()
+                    .frame(egui::Frame::none())
+                    .show(ctx, |ui| widget.ui(ui, &mut state));
+            });
+        }
+        state.currently_showing() != widget.data_bounds(&state)
+    };
+    let zooms = |scroll_zoom_sensitivity, deltas: &[f32]| {
+        zooms_with(scroll_zoom_sensitivity, deltas, false)
+    };
+    assert!(zooms(50., &[50.]));
+    assert!(!zooms(200., &[50.]));
+    // smaller deltas are accumulated during a gesture
+    assert!(!zooms(50., &[30.]));
+    assert!(zooms(50., &[30., 30.]));
+    // and dropped when the gesture ends
+    assert!(!zooms(50., &[30., 0., 30.]));
+    // zoom gestures (pinch, ctrl+scroll) use the same sensitivity
+    assert!(zooms_with(50., &[50.], true));
+    assert!(!zooms_with(200., &[50.], true));
+    assert!(!zooms_with(50., &[30.], true));
+    assert!(zooms_with(50., &[30., 30.], true));
+}
+
+#[test]
+fn shift_scroll_pan_test() {
+    let ctx = egui::Context::default();
+    let mut widget = MultiBitmapWidget::with_settings(
+        vec![(0, Data::example(20, 20, CoordinatePoint { x: 0, y: 0 }))],
+        MultiBitmapWidgetSettings {
+            colorbar: None,
+            horizontal_scroll_factor: 1.,
+            scroll_zoom_sensitivity: 1.,
+            shift_scroll_pans: true,
+            ..test_settings()
+        },
+    );
+    let mut state = widget.default_state_english();
+    run_frame(&ctx, &mut widget, &mut state, [60., 60.]);
+    let rect = || CoordinateRect {
+        left_top: CoordinatePoint { x: 5, y: 5 },
+        right_bottom: CoordinatePoint { x: 10, y: 10 },
+    };
+    assert!(state.set_shown(rect()));
+    state.events();
+    let mut scroll = |delta: f32| {
+        let input = egui::RawInput {
+            screen_rect: Some(egui::Rect::from_min_size(
+                egui::Pos2::ZERO,
+                egui::vec2(60., 60.),
+            )),
+            events: vec![
+                egui::Event::PointerMoved(egui::pos2(30., 30.)),
+                egui::Event::Scroll(egui::vec2(delta, 0.)),
+            ],
+            modifiers: egui::Modifiers::SHIFT,
+            ..Default::default()
+        };
+        let _ = ctx.run(input, |ctx| {
+            egui::CentralPanel::default()
+                .frame(egui::Frame::none())
+                .show(ctx, |ui| widget.ui(ui, &mut state));
+        });
+        state.take_view_change().map(|shown| shown.left_top.x)
+    };
+    // small deltas (e.g. from trackpads) are accumulated to a whole point, and the change is reported
+    assert_eq!(scroll(0.4), None);
+    assert_eq!(scroll(0.4), None);
+    assert_eq!(scroll(0.4), Some(4));
+}
+
+#[test]
+fn home_key_test() {
+    let resets_view = |navigation| {
+        let ctx = egui::Context::default();
+        let mut widget = MultiBitmapWidget::with_settings(
+            vec![(0, Data::example(20, 20, CoordinatePoint { x: 0, y: 0 }))],
+            MultiBitmapWidgetSettings {
+                colorbar: None,
+                navigation,
+                ..test_settings()
+            },
+        );
+        let mut state = widget.default_state_english();
+        run_frame(&ctx, &mut widget, &mut state, [60., 60.]);
+        assert!(state.set_shown(CoordinateRect {
+            left_top: CoordinatePoint { x: 1, y: 2 },
+            right_bottom: CoordinatePoint { x: 3, y: 4 },
+        }));
+        let input = |events| egui::RawInput {
+            screen_rect: Some(egui::Rect::from_min_size(
+                egui::Pos2::ZERO,
+                egui::vec2(60., 60.),
+            )),
+            events,
+            ..Default::default()
+        };
+        for events in [
+            vec![egui::Event::PointerMoved(egui::pos2(30., 30.))],
+            vec![egui::Event::Key {
+                key: egui::Key::Home,
+                pressed: true,
+                repeat: false,
+                modifiers: egui::Modifiers::NONE,
+            }],
+        ] {
+            let _ = ctx.run(input(events), |ctx| {
+                egui::CentralPanel::default()
+                    .frame(egui::Frame::none())
+                    .show(ctx, |ui| widget.ui(ui, &mut state));
+            });
+        }
+        state.currently_showing() == widget.data_bounds(&state)
+    };
+    assert!(resets_view(NavigationConfig::default()));
+    assert!(resets_view(NavigationConfig {
+        zoom_enabled: false,
+        ..Default::default()
+    }));
+    // a fixed view stays fixed
+    assert!(!resets_view(NavigationConfig {
+        zoom_enabled: false,
+        pan_enabled: false,
+        ..Default::default()
+    }));
+}
+
+#[test]
+fn take_view_change_test() {
+    let ctx = egui::Context::default();
+    let mut widget = MultiBitmapWidget::with_settings(
+        vec![(0, Data::example(4, 4, CoordinatePoint { x: 0, y: 0 }))],
+        test_settings(),
+    );
+    let mut state = widget.default_state_english();
+    run_frame(&ctx, &mut widget, &mut state, [60., 40.]);
+    state.events();
+    assert_eq!(state.take_view_change(), None);
+    state.events.push(Event::Selection);
+    state.events.push(Event::ShowRectangle);
+    state.events.push(Event::ShowRectangle);
+    assert_eq!(state.take_view_change(), state.currently_showing());
+    assert!(state.take_view_change().is_none());
+    assert!(matches!(state.events().as_slice(), [Event::Selection]));
+}
+
+#[test]
+fn render_size_test() {
+    let ctx = egui::Context::default();
+    let mut widget = MultiBitmapWidget::with_settings(
+        vec![
+            (0, Data::example(4, 4, CoordinatePoint { x: 0, y: 0 })),
+            (1, Data::example(4, 4, CoordinatePoint { x: 0, y: 0 })),
+        ],
+        test_settings(),
+    );
+    let mut state = widget.default_state_english();
+    run_frame(&ctx, &mut widget, &mut state, [60., 40.]);
+    assert_eq!(widget.effective_render_size(), [60, 40]);
+    let [_, _, width, height] = widget
+        .showmap
+        .subplot_rect(&1, [60, 40], &state.multimap)
+        .unwrap();
+    assert_eq!(widget.plot_area_size(&state), Some([width, height]));
+}
+
+#[test]
+fn letterbox_color_test() {
+    let ctx = egui::Context::default();
+    let mut widget = MultiBitmapWidget::with_settings(
+        vec![(0, Data::example(4, 4, CoordinatePoint { x: 0, y: 0 }))],
+        MultiBitmapWidgetSettings {
+            keep_aspect_ratio: true,
+            letterbox_color: Some(Color::DARK_GRAY),
+            ..test_settings()
+        },
+    );
+    let mut state = widget.default_state_english();
+    run_frame(&ctx, &mut widget, &mut state, [100., 40.]);
+    // the plot is centered, the padding is not part of the image
+    let rect = widget.last_image_rect.unwrap();
+    assert!(rect.width() < 100.);
+    assert!((rect.center() - egui::pos2(50., 20.)).length() <= 1.);
+    let input = |x| egui::RawInput {
+        screen_rect: Some(egui::Rect::from_min_size(
+            egui::Pos2::ZERO,
+            egui::vec2(100., 40.),
+        )),
+        events: vec![egui::Event::PointerMoved(egui::pos2(x, 20.))],
+        ..Default::default()
+    };
+    for (x, hovering) in [(2., false), (50., true)] {
+        let _ = ctx.run(input(x), |ctx| {
+            egui::CentralPanel::default()
+                .frame(egui::Frame::none())
+                .show(ctx, |ui| widget.ui(ui, &mut state));
+        });
+        assert_eq!(
+            !matches!(state.hover(), MultiMapPosition::NotHovering),
+            hovering
+        );
+    }
+}
+
+#[test]
+fn from_heatmaps_test() {
+    let heatmap = |value| crate::HeatmapData {
+        width: 3,
+        height: 2,
+        pixels: vec![value, value, f32::NAN, value, value, value],
+    };
+    let mut widget = MultiBitmapWidget::from_heatmaps(
+        vec![(0, heatmap(0.)), (1, heatmap(10.))],
+        (0., 10.),
+        crate::colors::ColorGradientOptions::StartEnd {
+            start: Color::BLUE,
+            end: Color::RED,
+            steps: 8,
+        },
+        crate::ScaleMode::Linear,
+        FontOptions::default(),
+        test_settings(),
+    )
+    .unwrap();
+    let point = |x, y| CoordinatePoint { x, y };
+    assert_eq!(widget.value_at(&1, &point(1, 1)), Some(10.));
+    assert_eq!(widget.value_at(&0, &point(0, 0)), Some(0.));
+    assert_eq!(widget.value_at(&0, &point(2, 0)), None);
+    assert_eq!(widget.value_at(&0, &point(3, 0)), None);
+    assert_eq!(widget.value_at(&2, &point(0, 0)), None);
+    // the colorbar uses the given limits
+    assert_eq!(
+        widget.showmap.colorbar_position(5., [60, 40]),
+        Some([[40., 20.], [60., 20.]])
+    );
+    widget.replace_data(&1, Data::example(3, 2, point(0, 0)));
+    assert_eq!(widget.value_at(&1, &point(1, 1)), None);
+    // invalid scales are rejected
+    assert!(MultiBitmapWidget::from_heatmaps(
+        vec![(0, heatmap(1.))],
+        (0., 10.),
+        crate::colors::ColorGradientOptions::StartEnd {
+            start: Color::BLUE,
+            end: Color::RED,
+            steps: 8,
+        },
+        crate::ScaleMode::SymLog { linthresh: 0. },
+        FontOptions::default(),
+        test_settings(),
+    )
+    .is_none());
+    // as are limits which cannot be scaled
+    assert!(MultiBitmapWidget::from_heatmaps(
+        vec![(0, heatmap(1.))],
+        (0., 10.),
+        crate::colors::ColorGradientOptions::StartEnd {
+            start: Color::BLUE,
+            end: Color::RED,
+            steps: 8,
+        },
+        crate::ScaleMode::Log10,
+        FontOptions::default(),
+        test_settings(),
+    )
+    .is_none());
+}
+
+#[test]
+fn missing_data_color_test() {
+    let contains_red = |missing_data_color| {
+        let widget = MultiBitmapWidget::from_heatmaps(
+            vec![(
+                0,
+                crate::HeatmapData {
+                    width: 2,
+                    height: 2,
+                    pixels: vec![0., 1., f32::NAN, 0.5],
+                },
+            )],
+            (0., 1.),
+            crate::colors::ColorGradientOptions::StartEnd {
+                start: Color::BLUE,
+                end: Color::GREEN,
+                steps: 8,
+            },
+            crate::ScaleMode::Linear,
+            FontOptions::default(),
+            MultiBitmapWidgetSettings {
+                colorbar: None,
+                missing_data_color,
+                ..test_settings()
+            },
+        )
+        .unwrap();
+        let mut state = widget.default_state_english();
+        let image = widget.render_color_image([40, 40], &mut state).unwrap();
+        assert_eq!(widget.value_at(&0, &CoordinatePoint { x: 0, y: 1 }), None);
+        image.pixels.contains(&Color::RED)
+    };
+    assert!(!contains_red(None));
+    assert!(contains_red(Some(Color::RED)));
+}
+
+#[test]
+fn bilinear_upsampling_test() {
+    let row = |upsampling| {
+        // no overlay texts
+        let data = crate::BitmapData {
+            width: 2,
+            height: 1,
+            pixels: vec![Color::BLACK, Color::WHITE],
+        }
+        .into_data(
+            CoordinatePoint { x: 0, y: 0 },
+            FontOptions::default(),
+            false,
+        );
+        let widget = MultiBitmapWidget::with_settings(
+            vec![(0, data)],
+            MultiBitmapWidgetSettings {
+                colorbar: None,
+                upsampling,
+                ..test_settings()
+            },
+        );
+        let mut state = widget.default_state_english();
+        let image = widget.render_color_image([80, 10], &mut state).unwrap();
+        image.pixels[5 * 80..6 * 80]
+            .iter()
+            .map(|c| c.r())
+            .collect::<Vec<_>>()
+    };
+    let bilinear = row(Upsampling::Bilinear);
+    // constant outside of the point centers, smooth in between
+    assert_eq!(bilinear[0], 0);
+    assert_eq!(bilinear[79], 255);
+    assert!(bilinear[20..60].windows(2).all(|w| w[0] <= w[1]));
+    assert!(bilinear[40] > 50 && bilinear[40] < 200);
+    // blocks of a single color, separated by boundaries
+    let nearest = row(Upsampling::Nearest);
+    let boundary = Color::GRAY.r();
+    assert!(nearest.iter().all(|r| [0, boundary, 255].contains(r)));
+    assert!(nearest.contains(&boundary) && !bilinear.contains(&boundary));
+}
+
+#[test]
+fn extrema_markers_test() {
+    let ctx = egui::Context::default();
+    let mut widget = MultiBitmapWidget::from_heatmaps(
+        vec![(
+            0,
+            crate::HeatmapData {
+                width: 4,
+                height: 4,
+                pixels: (0..16).map(|x| x as f32).collect(),
+            },
+        )],
+        (0., 15.),
+        crate::colors::ColorGradientOptions::StartEnd {
+            start: Color::BLUE,
+            end: Color::RED,
+            steps: 8,
+        },
+        crate::ScaleMode::Linear,
+        FontOptions::default(),
+        MultiBitmapWidgetSettings {
+            colorbar: None,
+            extrema_markers: Some(ExtremaMarkers {
+                min_color: Color::WHITE,
+                max_color: Color::BLACK,
+                size: 6.,
+            }),
+            ..test_settings()
+        },
+    )
+    .unwrap();
+    let mut state = widget.default_state_english();
+    run_frame(&ctx, &mut widget, &mut state, [40., 40.]);
+    let (min, max) = widget.source_values[0].extrema.unwrap();
+    assert_eq!(
+        (min, max),
+        (BitMapPoint { x: 0, y: 0 }, BitMapPoint { x: 3, y: 3 })
+    );
+    let position = |widget: &MultiBitmapWidget<i32>, state: &ShowState<i32>, x, y| {
+        widget.point_position(&0, &CoordinatePoint { x, y }, state)
+    };
+    assert_eq!(position(&widget, &state, 0, 0), Some(egui::pos2(5., 5.)));
+    assert_eq!(position(&widget, &state, 3, 3), Some(egui::pos2(35., 35.)));
+    // markers stay attached to their cells and are hidden if these are not shown
+    state.set_shown(CoordinateRect {
+        left_top: CoordinatePoint { x: 2, y: 2 },
+        right_bottom: CoordinatePoint { x: 4, y: 4 },
+    });
+    run_frame(&ctx, &mut widget, &mut state, [40., 40.]);
+    assert_eq!(position(&widget, &state, 0, 0), None);
+    assert_eq!(position(&widget, &state, 3, 3), Some(egui::pos2(30., 30.)));
+}
+
+#[test]
+fn copy_to_clipboard_render_problem_test() {
+    let mut widget = MultiBitmapWidget::with_settings(
+        vec![(0, Data::example(4, 4, CoordinatePoint { x: 0, y: 0 }))],
+        test_settings(),
+    );
+    let mut state = widget.default_state_english();
+    // too narrow for the colorbar, so rendering fails instead of panicking
+    let result = widget.copy_to_clipboard([5., 40.], &mut state);
+    assert!(matches!(result, Err(RenderProblem::ClipboardIssue(_))));
+    assert!(widget.clipboard_pending.is_none());
+    assert!(state.events().is_empty());
+}
+
+#[test]
+fn render_color_image_test() {
+    let ctx = egui::Context::default();
+    let mut widget = MultiBitmapWidget::with_settings(
+        vec![(0, Data::example(4, 4, CoordinatePoint { x: 0, y: 0 }))],
+        test_settings(),
+    );
+    let mut state = widget.default_state_english();
+    run_frame(&ctx, &mut widget, &mut state, [60., 40.]);
+    let image = widget.render_color_image([60, 40], &mut state).unwrap();
+    assert_eq!(image.size, [60, 40]);
+    assert_eq!(Some(image.pixels.as_slice()), widget.last_rendered());
+    let image = widget.render_color_image([30, 20], &mut state).unwrap();
+    assert_eq!(image.pixels.len(), 30 * 20);
+    assert_eq!(widget.last_rendered_size(), Some([60, 40]));
+    assert!(matches!(
+        widget.render_color_image([0, 20], &mut state),
+        Err(RenderProblem::WidthSmallerThanColorBar | RenderProblem::CountIsZero)
+    ));
+}
+
+#[test]
+fn selection_history_test() {
+    let widget = MultiBitmapWidget::<usize>::with_settings(Vec::new(), test_settings());
+    let mut state = widget.default_state_english();
+    let point = || [CoordinatePoint { x: 1, y: 2 }].into_iter().collect();
+    // repeated calls do not change the selection, so they are not stored for undo
+    state.make_selected(point());
+    state.make_selected(point());
+    state.clear_selected();
+    state.clear_selected();
+    assert!(!state.unselect_all());
+    assert!(!state.retain_selected(|_| true));
+    assert!(state.undo_selection());
+    assert_eq!(state.selected(), &point());
+    assert!(state.undo_selection());
+    assert!(state.selected().is_empty());
+    assert!(!state.undo_selection());
+}
+
+#[test]
+fn select_row_column_test() {
+    let mut widget = MultiBitmapWidget::with_settings(
+        vec![
+            (0, Data::example(3, 2, CoordinatePoint { x: 0, y: 0 })),
+            (1, Data::example(2, 2, CoordinatePoint { x: 2, y: 1 })),
+        ],
+        MultiBitmapWidgetSettings {
+            max_selected: Some(4),
+            ..test_settings()
+        },
+    );
+    let mut state = widget.default_state_english();
+    let point = |x, y| CoordinatePoint { x, y };
+    // row 1 is covered by both data sets, the shared point (2|1) is selected once
+    widget.select_row(1, false, &mut state);
+    assert_eq!(
+        state.selected_sorted(),
+        vec![point(0, 1), point(1, 1), point(2, 1), point(3, 1)]
+    );
+    assert!(state.events().iter().any(|e| matches!(e, Event::Selection)));
+    widget.select_column(3, true, &mut state);
+    assert_eq!(state.selected().len(), 4);
+    assert!(state
+        .events()
+        .iter()
+        .any(|e| matches!(e, Event::SelectionLimitReached)));
+    widget.max_selected = None;
+    widget.select_column(0, false, &mut state);
+    assert_eq!(state.selected_sorted(), vec![point(0, 0), point(0, 1)]);
+}
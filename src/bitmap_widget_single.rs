@@ -1,8 +1,6 @@
 use std::fmt::Debug;
 
-pub use crate::multimap::{
-    BitMapText, ColorWithThickness, CoordinatePoint, Data, FontOptions, Overlay, RenderProblem,
-};
+pub use crate::multimap::{CoordinatePoint, Data, RenderProblem};
 use crate::MultiBitmapWidget;
 use egui::Color32 as Color;
 
@@ -16,7 +14,7 @@ pub enum MapPosition {
     /// Mouse is hoverinlg over data area, containing the point in data coordinates
     Pixel(CoordinatePoint),
     /// Mouse is over Colorbar
-    Colorbar(f32),
+    Colorbar(f64),
 }
 impl From<crate::MultiMapPosition<()>> for MapPosition {
     fn from(value: crate::MultiMapPosition<()>) -> Self {
@@ -41,14 +39,166 @@ impl BitmapWidget {
             map: MultiBitmapWidget::with_settings(vec![((), data)], settings),
         }
     }
+    /// Constructor using numeric data, which is converted to colors using the gradient and the limits (see 'HeatmapData::to_bitmap').
+    /// The data starts at coordinate (0, 0) and gets an overlay without texts, using the given font for the coordinates.
+    /// If the settings contain a colorbar, its gradient and limits are replaced by the given ones.
+    /// The data is scaled (e.g. logarithmic) before the conversion, and the colorbar uses the same scale.
+    /// The values are kept for 'value_at' and the colorbar marker, which costs 4 bytes per data point in addition to the colors
+    /// Returns None if the scale or the limits are invalid (see 'ScaleMode::is_valid_limits') or the gradient has no colors
+    pub fn from_heatmap(
+        data: crate::HeatmapData,
+        limits: (f32, f32),
+        gradient: crate::colors::ColorGradientOptions,
+        scale: crate::ScaleMode,
+        font: crate::FontOptions,
+        settings: crate::MultiBitmapWidgetSettings,
+    ) -> Option<Self> {
+        MultiBitmapWidget::from_heatmaps(vec![((), data)], limits, gradient, scale, font, settings)
+            .map(|map| Self { map })
+    }
+    /// Get the numeric value of a data point, if the widget was created by 'from_heatmap' and there is a value at this point.
+    /// Replacing the data via 'replace_data' removes the values
+    pub fn value_at(&self, point: &CoordinatePoint) -> Option<f32> {
+        self.map.value_at(&(), point)
+    }
     /// Get default state, in english
     pub fn default_state_english(&self) -> ShowStateSingle {
+        self.default_state(crate::Localization::english())
+    }
+    /// Get default state, using the given texts
+    pub fn default_state(&self, localization: crate::Localization) -> ShowStateSingle {
         ShowStateSingle {
-            state: self.map.default_state_english(),
+            state: self.map.default_state(localization),
         }
     }
-    /// Show widget
-    pub fn ui(&mut self, ui: &mut egui::Ui, state: &mut ShowStateSingle) {
+    /// Set a callback, which is invoked with the hovered position on each double click.
+    /// This is called in addition to re-centering, unless 'double_click_recenters' is disabled
+    pub fn set_double_click_callback(&mut self, mut callback: impl FnMut(MapPosition) + 'static) {
+        self.map
+            .set_double_click_callback(move |position| callback(position.clone().into()))
+    }
+    /// Remove the double click callback
+    pub fn clear_double_click_callback(&mut self) {
+        self.map.clear_double_click_callback()
+    }
+    /// Set a function returning the value of a data point, if any.
+    /// While a data point is hovered, its value is marked on the colorbar (using the color 'colorbar_marker')
+    pub fn set_value_lookup(&mut self, lookup: impl Fn(&CoordinatePoint) -> Option<f64> + 'static) {
+        self.map.set_value_lookup(move |(), point| lookup(point))
+    }
+    /// Remove the value lookup
+    pub fn clear_value_lookup(&mut self) {
+        self.map.clear_value_lookup()
+    }
+    /// Check if a change (e.g. new data, an animation) is not yet shown.
+    /// Apps only repainting on demand should call 'request_repaint' in this case.
+    /// See also 'ShowStateSingle::needs_repaint'
+    pub fn needs_repaint(&self) -> bool {
+        self.map.needs_repaint()
+    }
+    /// Show the whole data, like the 'Home' key does.
+    /// In contrast to the key, this is not animated. Emits 'Event::ShowRectangle' if the shown rectangle changes
+    pub fn home(&mut self, state: &mut ShowStateSingle) {
+        self.map.home(&mut state.state)
+    }
+    /// Zoom in (positive increment) or out (negative increment) by the given number of points per side, like the '+'/'-' keys do.
+    /// This is not animated. Emits 'Event::ShowRectangle' if the shown rectangle changes. Nothing happens before the first render
+    pub fn zoom(&mut self, increment: i32, state: &mut ShowStateSingle) {
+        self.map.zoom(increment, &mut state.state)
+    }
+    /// Move the shown rectangle by the given number of points, like dragging does.
+    /// This is not animated. Emits 'Event::ShowRectangle' if the shown rectangle changes. Nothing happens before the first render
+    pub fn translate(&mut self, delta: CoordinatePoint, state: &mut ShowStateSingle) {
+        self.map.translate(delta, &mut state.state)
+    }
+    /// Center the shown rectangle at the given point, keeping its size, like double-clicking does.
+    /// This is not animated. Emits 'Event::ShowRectangle' if the shown rectangle changes. Nothing happens before the first render
+    pub fn center_to(&mut self, point: &CoordinatePoint, state: &mut ShowStateSingle) {
+        self.map.center_to(point, &mut state.state)
+    }
+    /// Get the bounding box of the data, i.e. the area shown after 'Home'
+    pub fn data_bounds(&self, state: &ShowStateSingle) -> Option<crate::CoordinateRect> {
+        self.map.data_bounds(&state.state)
+    }
+    /// Select all points of the given row (shift+click).
+    /// If 'additive' is false, the previous selection is cleared. 'max_selected' is respected
+    pub fn select_row(&self, y: i32, additive: bool, state: &mut ShowStateSingle) {
+        self.map.select_row(y, additive, &mut state.state)
+    }
+    /// Select all points of the given column (alt+click).
+    /// If 'additive' is false, the previous selection is cleared. 'max_selected' is respected
+    pub fn select_column(&self, x: i32, additive: bool, state: &mut ShowStateSingle) {
+        self.map.select_column(x, additive, &mut state.state)
+    }
+    /// Get the size in pixels ([width, height]) the widget renders at, including the colorbar.
+    /// This is the size of the most recent call of 'ui' (or the start size)
+    pub fn effective_render_size(&self) -> [usize; 2] {
+        self.map.effective_render_size()
+    }
+    /// Get the size in pixels ([width, height]) of the plot (excluding the colorbar) when rendering at 'effective_render_size'
+    pub fn plot_area_size(&self, state: &ShowStateSingle) -> Option<[usize; 2]> {
+        self.map.plot_area_size(&state.state)
+    }
+    /// Get the on-screen rectangle of the plot (excluding the colorbar), e.g. to draw annotations.
+    /// This uses the layout of the most recent call of 'ui'
+    pub fn plot_rect(&self, state: &ShowStateSingle) -> Option<egui::Rect> {
+        self.map.subplot_rect(&(), &state.state)
+    }
+    /// Replace the data set, e.g. to show a new frame.
+    /// The shown area is kept. Selected points outside of the new data set are unselected
+    pub fn replace_data(&mut self, data: Data<Color>, state: &mut ShowStateSingle) {
+        state.state.retain_selected(|point| data.contains(point));
+        self.map.replace_data(&(), data);
+    }
+    /// Change which navigation gestures are enabled
+    pub fn set_navigation(&mut self, navigation: crate::NavigationConfig) {
+        self.map.set_navigation(navigation)
+    }
+    /// Get which navigation gestures are enabled
+    pub fn navigation(&self) -> crate::NavigationConfig {
+        self.map.navigation()
+    }
+    /// Show or hide the colorbar. The currently shown area is kept
+    pub fn set_colorbar_visible(&mut self, visible: bool) {
+        self.map.set_colorbar_visible(visible)
+    }
+    /// Check if the colorbar is shown (if there is any)
+    pub fn colorbar_visible(&self) -> bool {
+        self.map.colorbar_visible()
+    }
+    /// Replace the colorbar (gradient, thickness, limits). Use 'None' to remove it
+    pub fn set_colorbar(
+        &mut self,
+        colorbar: Option<(crate::colors::Gradient<Color>, usize, (f64, f64))>,
+    ) {
+        self.map.set_colorbar(colorbar)
+    }
+    /// Change the value range shown by the colorbar, e.g. after updating the data.
+    /// Returns false (and changes nothing) if there is no colorbar or if lower < upper does not hold
+    pub fn set_colorbar_limits(&mut self, lower: f64, upper: f64) -> bool {
+        self.map.set_colorbar_limits(lower, upper)
+    }
+    /// Get the pixels of the most recent successful render, row by row.
+    /// This is kept until the next render completes. In case of a render problem, this is None
+    pub fn last_rendered(&self) -> Option<&[Color]> {
+        self.map.last_rendered()
+    }
+    /// Get the size ([width, height]) of the most recent successful render
+    pub fn last_rendered_size(&self) -> Option<[usize; 2]> {
+        self.map.last_rendered_size()
+    }
+    /// Render at the given size ([width, height] in pixels) without uploading a texture, e.g. to show the heatmap in an own 'egui::Image'.
+    /// The extrema markers, the colorbar marker and the letterbox padding are painted by 'ui' only, so they are not part of this image.
+    /// The widget itself is not affected, and the render problem of the state is not changed
+    pub fn render_color_image(
+        &self,
+        size: [usize; 2],
+        state: &mut ShowStateSingle,
+    ) -> Result<egui::ColorImage, RenderProblem> {
+        self.map.render_color_image(size, &mut state.state)
+    }
+    /// Show widget. Returns the response of the underlying image
+    pub fn ui(&mut self, ui: &mut egui::Ui, state: &mut ShowStateSingle) -> egui::Response {
         self.map.ui(ui, &mut state.state)
     }
 }
@@ -59,33 +209,113 @@ pub struct ShowStateSingle {
 }
 impl ShowStateSingle {
     /// Select the given positions and only those
-    pub fn make_selected(&mut self, selected:std::collections::HashSet<CoordinatePoint>){
+    pub fn make_selected(&mut self, selected: std::collections::HashSet<CoordinatePoint>) {
         self.state.make_selected(selected)
     }
     /// Clear selected positions
-    pub fn clear_selected(&mut self){
+    pub fn clear_selected(&mut self) {
         self.state.clear_selected()
     }
+    /// Unselect all points outside of the given rectangle. Emits 'Event::Selection' if anything was unselected.
+    /// Returns true if the selection changed
+    pub fn prune_selection_to(&mut self, rect: &crate::CoordinateRect) -> bool {
+        self.state.prune_selection_to(rect)
+    }
+    /// Undo the last change of the selection. Returns false if there is nothing to undo
+    pub fn undo_selection(&mut self) -> bool {
+        self.state.undo_selection()
+    }
+    /// Redo the last undone change of the selection. Returns false if there is nothing to redo
+    pub fn redo_selection(&mut self) -> bool {
+        self.state.redo_selection()
+    }
+    /// Show the whole data during the next 'ui' call, like the 'Home' key does. This emits 'Event::ShowRectangle'
+    pub fn request_home(&mut self) {
+        self.state.request_home()
+    }
+    /// Check if a change of this state (e.g. of the selection) is not yet shown
+    pub fn needs_repaint(&self) -> bool {
+        self.state.needs_repaint()
+    }
+    /// Get the texts used in the context menu
+    pub fn localization(&self) -> &crate::Localization {
+        self.state.localization()
+    }
+    /// Change the texts used in the context menu, e.g. to switch the language at runtime
+    pub fn set_localization(&mut self, localization: crate::Localization) {
+        self.state.set_localization(localization)
+    }
     /// Get events
     pub fn events(&mut self) -> Vec<crate::Event<()>> {
         self.state.events()
     }
+    /// Get the events without removing them, e.g. to log them while another part of the app handles them via 'events'
+    pub fn peek_events(&self) -> &[crate::Event<()>] {
+        self.state.peek_events()
+    }
+    /// Get the navigation events (i.e. 'Event::ShowRectangle') only. All other events are kept for 'events'.
+    /// To link several widgets, use 'take_view_change'
+    pub fn take_navigation_events(&mut self) -> Vec<crate::Event<()>> {
+        self.state.take_navigation_events()
+    }
+    /// The shown rectangle, if the view was changed (i.e. there was an 'Event::ShowRectangle') since the last call.
+    /// This removes the navigation events, all other events are kept for 'events'.
+    ///
+    /// Linking two widgets, e.g. two channels of the same scan, works like this (after calling 'ui' of both):
+    /// ```
+    /// fn link(a: &mut egui_heatmap::ShowStateSingle, b: &mut egui_heatmap::ShowStateSingle) {
+    ///     if let Some(rect) = a.take_view_change() {
+    ///         b.set_shown(rect);
+    ///     } else if let Some(rect) = b.take_view_change() {
+    ///         a.set_shown(rect);
+    ///     }
+    /// }
+    /// ```
+    /// Since 'set_shown' does not emit any event, this does not cause a feedback loop
+    pub fn take_view_change(&mut self) -> Option<crate::CoordinateRect> {
+        self.state.take_view_change()
+    }
     /// Get the currently selected points
     pub fn selected(&self) -> &std::collections::HashSet<CoordinatePoint> {
         self.state.selected()
     }
+    /// Get the currently selected points, sorted by row and then by column
+    pub fn selected_sorted(&self) -> Vec<CoordinatePoint> {
+        self.state.selected_sorted()
+    }
     /// Fetch rectangle which is currently shown
     pub fn currently_showing(&self) -> Option<crate::CoordinateRect> {
         self.state.currently_showing()
     }
+    /// Show the given rectangle, e.g. to restore a saved view or to mirror another widget.
+    /// This does not emit 'Event::ShowRectangle', so copying the rectangle between linked widgets cannot cause a feedback loop.
+    /// Returns false (and changes nothing) if the rectangle does not contain any point.
+    /// This is the setter of the shown rectangle, i.e. the counterpart of 'currently_showing'
+    #[doc(alias = "set_shown_rectangle")]
+    pub fn set_shown(&mut self, rect: crate::CoordinateRect) -> bool {
+        self.state.set_shown(rect)
+    }
+    /// Zoom to the bounding box of the selected points, expanded by a small margin.
+    /// Emits 'Event::ShowRectangle'. Returns false (and changes nothing) if nothing is selected
+    pub fn zoom_to_selection(&mut self) -> bool {
+        self.state.zoom_to_selection()
+    }
     /// Check if there was an issue will rendering
     pub fn render_problem(&self) -> Option<&RenderProblem> {
         self.state.render_problem()
     }
+    /// Forget the current render problem. Note that each successful render clears it, too
+    pub fn clear_render_problem(&mut self) {
+        self.state.clear_render_problem()
+    }
     /// Check if position was clicked
     pub fn clicked(&self) -> Option<MapPosition> {
         self.state.clicked().cloned().map(Into::into)
     }
+    /// Check if position was double-clicked
+    pub fn double_clicked(&self) -> Option<MapPosition> {
+        self.state.double_clicked().cloned().map(Into::into)
+    }
     /// Check if position was clicked
     pub fn hover(&self) -> MapPosition {
         self.state.hover().clone().into()
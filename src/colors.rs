@@ -61,11 +61,115 @@ pub enum ColorGradientOptions {
         /// steps
         steps: usize,
     },
+    /// Piecewise gradient between arbitrary stops, each with a position in [0.0, 1.0].
+    /// Positions are clamped to [0.0, 1.0] and sorted, non-finite positions are ignored
+    Stops {
+        /// Position and color of each stop
+        stops: Vec<(f32, Color)>,
+        /// steps
+        steps: usize,
+    },
+    /// One of the well-known perceptual colormaps, sampled into 'steps' colors
+    Named {
+        /// Colormap
+        colormap: NamedColormap,
+        /// steps
+        steps: usize,
+    },
+}
+/// Perceptual colormaps known from matplotlib
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum NamedColormap {
+    /// From dark blue via green to yellow
+    Viridis,
+    /// From black via purple to light yellow
+    Magma,
+    /// From black via purple and orange to light yellow
+    Inferno,
+    /// From dark blue via magenta to yellow
+    Plasma,
+}
+impl NamedColormap {
+    /// Control points of the colormap, equally spaced from lowest to highest value
+    pub fn control_points(&self) -> [Color; 10] {
+        let hex = match self {
+            NamedColormap::Viridis => [
+                0x440154, 0x482878, 0x3e4a89, 0x31688e, 0x26828e, 0x1f9e89, 0x35b779, 0x6dcd59,
+                0xb4de2c, 0xfde725,
+            ],
+            NamedColormap::Magma => [
+                0x000004, 0x180f3e, 0x451077, 0x721f81, 0x9f2f7f, 0xcd4071, 0xf1605d, 0xfd9567,
+                0xfec98d, 0xfcfdbf,
+            ],
+            NamedColormap::Inferno => [
+                0x000004, 0x1b0c42, 0x4b0c6b, 0x781c6d, 0xa52c60, 0xcf4446, 0xed6925, 0xfb9a06,
+                0xf7d03c, 0xfcffa4,
+            ],
+            NamedColormap::Plasma => [
+                0x0d0887, 0x47039f, 0x7301a8, 0x9c179e, 0xbd3786, 0xd8576b, 0xed7953, 0xfa9e3b,
+                0xfdc926, 0xf0f921,
+            ],
+        };
+        hex.map(|x: u32| Color::from_rgb((x >> 16) as u8, (x >> 8) as u8, x as u8))
+    }
+    /// Sample the colormap into 'steps' colors, interpolating between the control points in Oklab
+    fn sample(&self, steps: usize) -> Vec<Color> {
+        let points = self.control_points();
+        let last = (points.len() - 1) as f32;
+        let stops = points
+            .iter()
+            .enumerate()
+            .map(|(i, c)| (i as f32 / last, *c))
+            .collect::<Vec<_>>();
+        sample_stops(&stops, steps)
+            .into_iter()
+            .map(|oklab| {
+                // exact inverse of 'convert_to_oklab', so the control points are reproduced
+                let rgb = oklab::oklab_to_linear_srgb(oklab);
+                Color::from(egui::Rgba::from_rgb(rgb.r, rgb.g, rgb.b))
+            })
+            .collect()
+    }
+}
+/// Sample piecewise-linear gradient between stops (sorted by position in [0.0, 1.0]) into 'steps' Oklab colors.
+/// Interpolation happens in Oklab, the caller chooses the conversion back
+fn sample_stops(stops: &[(f32, Color)], steps: usize) -> Vec<Oklab> {
+    let stops = stops
+        .iter()
+        .map(|(t, c)| (*t, convert_to_oklab(c)))
+        .collect::<Vec<_>>();
+    let (first, last) = match (stops.first(), stops.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return Vec::new(),
+    };
+    (0..steps)
+        .map(|i| {
+            let t = if steps == 1 {
+                0.5
+            } else {
+                i as f32 / (steps - 1) as f32
+            };
+            if t <= first.0 {
+                first.1
+            } else if t >= last.0 {
+                last.1
+            } else {
+                let upper = stops.partition_point(|(position, _)| *position <= t);
+                let ((t0, start), (t1, end)) = (&stops[upper - 1], &stops[upper]);
+                let i = (t - t0) / (t1 - t0);
+                Oklab {
+                    l: interpolate_single_channel(start.l, end.l, 1., i),
+                    a: interpolate_single_channel(start.a, end.a, 1., i),
+                    b: interpolate_single_channel(start.b, end.b, 1., i),
+                }
+            }
+        })
+        .collect()
 }
 fn convert_to_oklab(egui: &Color) -> Oklab {
     let rgba = egui::Rgba::from(*egui);
     let [r, g, b, _a] = rgba.to_array();
-    oklab::linear_srgb_to_oklab(oklab::RGB { r, g, b })
+    oklab::linear_srgb_to_oklab(oklab::Rgb { r, g, b })
 }
 /// Convert an Oklab color to an egui-Color
 pub fn convert_from_oklab(oklab: Oklab) -> Color {
@@ -101,9 +205,37 @@ fn gradient(start: &Color, end: &Color, steps: usize) -> Vec<Color> {
     }
 }
 
+/// Relative position of a value on a colorbar with the given limits, i.e. 0.0 at 'lower' and 1.0 at 'upper'.
+/// The result is clamped to [0.0, 1.0]. Returns NaN if the value is NaN or the limits are not finite with lower < upper
+pub fn value_to_fraction(lower: f64, upper: f64, value: f64) -> f32 {
+    if !(lower.is_finite() && upper.is_finite() && lower < upper) {
+        return f32::NAN;
+    }
+    ((value - lower) / (upper - lower)).clamp(0., 1.) as f32
+}
+
 /// Color Gradient
+#[derive(serde::Deserialize, serde::Serialize)]
 pub struct Gradient<C>(pub(crate) Vec<C>);
 impl<C: Clone> Gradient<C> {
+    /// Use the given colors, from lowest to highest value. Returns None if there are no colors
+    pub fn from_colors(colors: Vec<C>) -> Option<Self> {
+        if colors.is_empty() {
+            None
+        } else {
+            Some(Self(colors))
+        }
+    }
+    /// This gradient with the colors in opposite order, i.e. from highest to lowest value
+    pub fn reversed(&self) -> Self {
+        let mut reversed = Self(self.0.clone());
+        reversed.reverse();
+        reversed
+    }
+    /// Reverse the order of the colors in place
+    pub fn reverse(&mut self) {
+        self.0.reverse();
+    }
     pub(crate) fn element_at(&self, row: usize, height: usize) -> C {
         self.0[row * self.0.len() / height].clone()
     }
@@ -121,32 +253,22 @@ impl<C: Clone> Gradient<C> {
         gradient[index].clone()
     }
 
-    pub(crate) fn fetch_value(&self, lower: f32, upper: f32, relative_distance: f32) -> f32 {
+    /// Value of the gradient step drawn at the given row (counted from the bottom), see 'element_at'
+    pub(crate) fn fetch_value(&self, lower: f64, upper: f64, row: usize, height: usize) -> f64 {
         let n = self.0.len();
-        if n == 0 {
-            f32::NAN
+        if n == 0 || height == 0 {
+            f64::NAN
         } else if n == 1 {
             (lower + upper) / 2.
         } else {
-            let relative_distance = if relative_distance < 0. {
-                0.
-            } else if relative_distance > 1. {
-                1.
-            } else {
-                relative_distance
-            };
-            let delta = (upper - lower) / ((n - 1) as f32);
-            let f = (relative_distance * n as f32).floor() * delta + lower;
-            if f > upper {
-                upper
-            } else {
-                f
-            }
+            let step = std::cmp::min(row * n / height, n - 1);
+            lower + (upper - lower) * (step as f64) / ((n - 1) as f64)
         }
     }
 }
 impl Gradient<Color> {
-    /// This computes a color gradient
+    /// This computes a color gradient.
+    /// The gradient is empty if the options give no colors (zero steps or no valid stops), see 'try_with_options'
     pub fn with_options(options: &ColorGradientOptions) -> Self {
         Self(match options {
             ColorGradientOptions::StartEnd { start, end, steps } => gradient(start, end, *steps),
@@ -182,6 +304,152 @@ impl Gradient<Color> {
                     start_center
                 }
             },
+            ColorGradientOptions::Stops { stops, steps } => {
+                let mut stops = stops
+                    .iter()
+                    .filter(|(position, _)| position.is_finite())
+                    .map(|(position, color)| (position.clamp(0., 1.), *color))
+                    .collect::<Vec<_>>();
+                stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+                sample_stops(&stops, *steps)
+                    .into_iter()
+                    .map(convert_from_oklab)
+                    .collect()
+            }
+            ColorGradientOptions::Named { colormap, steps } => colormap.sample(*steps),
         })
     }
+    /// This computes a color gradient. Returns None if the options give no colors, like 'from_colors'
+    pub fn try_with_options(options: &ColorGradientOptions) -> Option<Self> {
+        Self::from_colors(Self::with_options(options).0)
+    }
+    /// Perceptual colormap 'viridis' with the given number of steps
+    pub fn viridis(steps: usize) -> Self {
+        Self(NamedColormap::Viridis.sample(steps))
+    }
+    /// Perceptual colormap 'magma' with the given number of steps
+    pub fn magma(steps: usize) -> Self {
+        Self(NamedColormap::Magma.sample(steps))
+    }
+    /// Perceptual colormap 'inferno' with the given number of steps
+    pub fn inferno(steps: usize) -> Self {
+        Self(NamedColormap::Inferno.sample(steps))
+    }
+    /// Perceptual colormap 'plasma' with the given number of steps
+    pub fn plasma(steps: usize) -> Self {
+        Self(NamedColormap::Plasma.sample(steps))
+    }
+}
+
+#[test]
+fn value_to_fraction_test() {
+    assert_eq!(value_to_fraction(-1., 3., -1.), 0.);
+    assert_eq!(value_to_fraction(-1., 3., 1.), 0.5);
+    assert_eq!(value_to_fraction(-1., 3., 3.), 1.);
+    assert_eq!(value_to_fraction(-1., 3., -5.), 0.);
+    assert_eq!(value_to_fraction(-1., 3., f64::INFINITY), 1.);
+    assert!(value_to_fraction(-1., 3., f64::NAN).is_nan());
+    assert!(value_to_fraction(3., 3., 3.).is_nan());
+    // inverse of the hover value of the colorbar
+    let gradient = Gradient(vec![0, 1, 2, 3, 4]);
+    for step in 0..5 {
+        let value = gradient.fetch_value(-1., 3., step, 5);
+        assert_eq!(value_to_fraction(-1., 3., value), step as f32 / 4.);
+    }
+}
+
+#[test]
+fn named_colormap_test() {
+    let close = |a: Color, b: Color| (0..4).all(|i| (a[i] as i32 - b[i] as i32).abs() <= 1);
+    let viridis = Gradient::viridis(10);
+    let points = NamedColormap::Viridis.control_points();
+    assert_eq!(viridis.0.len(), 10);
+    assert!(viridis.0.iter().zip(points).all(|(&a, b)| close(a, b)));
+    let magma = Gradient::magma(256);
+    assert_eq!(magma.0.len(), 256);
+    assert!(close(magma.0[0], Color::from_rgb(0, 0, 4)));
+    assert!(close(magma.0[255], Color::from_rgb(0xfc, 0xfd, 0xbf)));
+    let plasma = Gradient::with_options(&ColorGradientOptions::Named {
+        colormap: NamedColormap::Plasma,
+        steps: 3,
+    });
+    assert!(close(plasma.0[0], Color::from_rgb(0x0d, 0x08, 0x87)));
+    assert!(close(plasma.0[2], Color::from_rgb(0xf0, 0xf9, 0x21)));
+    assert_eq!(Gradient::inferno(1).0.len(), 1);
+    assert!(Gradient::inferno(0).0.is_empty());
+}
+
+#[test]
+fn stops_gradient_test() {
+    // like 'StartEnd', the colors are additive, so only compare the color channels
+    let close = |a: Color, b: Color| (0..3).all(|i| (a[i] as i32 - b[i] as i32).abs() <= 1);
+    let gradient = Gradient::with_options(&ColorGradientOptions::Stops {
+        stops: vec![
+            (1.5, Color::WHITE),
+            (0.5, Color::RED),
+            (-1., Color::BLUE),
+            (f32::NAN, Color::GREEN),
+        ],
+        steps: 5,
+    });
+    assert_eq!(gradient.0.len(), 5);
+    assert!(close(gradient.0[0], Color::BLUE));
+    assert!(close(gradient.0[2], Color::RED));
+    assert!(close(gradient.0[4], Color::WHITE));
+    assert!(!close(gradient.0[1], Color::BLUE) && !close(gradient.0[1], Color::RED));
+    // stops not covering the whole range extend with their colors
+    let gradient = Gradient::with_options(&ColorGradientOptions::Stops {
+        stops: vec![(0.25, Color::RED), (0.75, Color::BLUE)],
+        steps: 5,
+    });
+    assert!(close(gradient.0[0], Color::RED));
+    assert!(close(gradient.0[4], Color::BLUE));
+    // two stops at the ends give the same colors as 'StartEnd'
+    let stops = Gradient::with_options(&ColorGradientOptions::Stops {
+        stops: vec![(0., Color::RED), (1., Color::BLUE)],
+        steps: 5,
+    });
+    let start_end = Gradient::with_options(&ColorGradientOptions::StartEnd {
+        start: Color::RED,
+        end: Color::BLUE,
+        steps: 5,
+    });
+    assert_eq!(stops.0, start_end.0);
+    // no valid stops give no gradient
+    assert!(Gradient::try_with_options(&ColorGradientOptions::Stops {
+        stops: Vec::new(),
+        steps: 5,
+    })
+    .is_none());
+    assert!(Gradient::try_with_options(&ColorGradientOptions::Stops {
+        stops: vec![(f32::NAN, Color::RED)],
+        steps: 5,
+    })
+    .is_none());
+    assert!(Gradient::try_with_options(&ColorGradientOptions::StartEnd {
+        start: Color::RED,
+        end: Color::BLUE,
+        steps: 0,
+    })
+    .is_none());
+}
+
+#[test]
+fn reversed_gradient_test() {
+    let gradient = Gradient(vec!['a', 'b', 'c']);
+    let reversed = gradient.reversed();
+    assert_eq!(reversed.0, vec!['c', 'b', 'a']);
+    assert_eq!(reversed.lookup_color(0.), 'c');
+    assert_eq!(reversed.lookup_color(1.), 'a');
+    // colorbar rows and their hover values stay consistent
+    for row in 0..6 {
+        assert_eq!(reversed.element_at(row, 6), gradient.element_at(5 - row, 6));
+        assert_eq!(
+            reversed.fetch_value(0., 2., row, 6),
+            gradient.fetch_value(0., 2., row, 6)
+        );
+    }
+    let mut twice = reversed;
+    twice.reverse();
+    assert_eq!(twice.0, gradient.0);
 }
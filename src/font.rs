@@ -1,5 +1,5 @@
 /// Font to use
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, serde::Deserialize, serde::Serialize)]
 pub enum Font {
     /// Use the highest-priority monospace font from egui
     #[default]
@@ -9,25 +9,52 @@ pub enum Font {
 }
 
 /// Options for rendering a string
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, serde::Deserialize, serde::Serialize)]
 pub struct FontOptions {
     /// Font to use
     pub font: Font,
     /// Is the background transparent? Otherwise, background is black.
     pub background_is_transparent: bool,
+    /// Only used if the background is transparent: Darken the data behind the text by this factor to improve readability.
+    /// Use 0 to keep the data unchanged, 1 for a black background
+    #[serde(default)]
+    pub background_opacity: f32,
     /// Height of font. Doubling this doubles the size of the rendered string (up to rounding/quantization)
     pub font_height: f32,
+    /// Lay out the text right-to-left, e.g. for Arabic or Hebrew.
+    /// Runs of right-to-left characters are always reversed; this additionally reverses the order of the runs, and right-aligns titles
+    #[serde(default)]
+    pub rtl: bool,
+    /// Only used for Font8x8: Scale the width relative to the height, e.g. 2 to get an aspect ratio similar to EguiMonospace.
+    /// Values below 1 are treated as 1
+    #[serde(default)]
+    pub horizontal_scale: f32,
 }
 impl FontOptions {
+    /// Render some text to a bitmap
+    pub fn render(&self, text: &str) -> Result<BitMapText, FontError> {
+        BitMapText::new(text, self)
+    }
     /// Render some text to a bitmap.
     /// Returns None in case of a problem
-    pub fn render(&self, text: &str) -> Option<BitMapText> {
-        BitMapText::new(text, self)
+    pub fn render_opt(&self, text: &str) -> Option<BitMapText> {
+        self.render(text).ok()
     }
 }
 
+/// Reasons why a text cannot be rendered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FontError {
+    /// The text is empty
+    EmptyText,
+    /// The font could not be loaded
+    FontUnavailable,
+    /// The rendered text has no visible width or height, e.g. only whitespace
+    ZeroSize,
+}
+
 /// A rendered gray-scale bitmap, representing a string rendered using some font
-#[derive(PartialEq)]
+#[derive(PartialEq, Clone, serde::Deserialize, serde::Serialize)]
 pub struct BitMapText {
     /// data of the bitmap
     pub data: Vec<u8>,
@@ -46,8 +73,15 @@ impl BitMapText {
             font_height,
             font,
             background_is_transparent: _,
+            background_opacity: _,
+            rtl,
+            horizontal_scale,
         }: &FontOptions,
-    ) -> Option<BitMapText> {
+    ) -> Result<BitMapText, FontError> {
+        if text.is_empty() {
+            return Err(FontError::EmptyText);
+        }
+        let text = &visual_order(text, *rtl);
         let fonts = egui::FontDefinitions::default();
         match &font {
             Font::EguiMonospace => {
@@ -57,7 +91,7 @@ impl BitMapText {
                     .and_then(|x| x.first())
                     .and_then(|label| fonts.font_data.get(label))
                     .and_then(|font| rusttype::Font::try_from_bytes(&font.font))
-                    .expect("Failed to retrieve egui default font");
+                    .ok_or(FontError::FontUnavailable)?;
                 /*fonts
                 .families
                 .get(&egui::FontFamily::Proportional)
@@ -105,6 +139,9 @@ impl BitMapText {
                     .next()
                     .unwrap_or(0.0)
                     .ceil() as usize;
+                if width == 0 || pixel_height == 0 {
+                    return Err(FontError::ZeroSize);
+                }
                 let mut data = vec![0; width * pixel_height];
                 for g in glyphs {
                     if let Some(bb) = g.pixel_bounding_box() {
@@ -123,7 +160,7 @@ impl BitMapText {
                     }
                 }
 
-                Some(Self {
+                Ok(Self {
                     data,
                     width: width as i32,
                     height: height as i32,
@@ -163,10 +200,10 @@ impl BitMapText {
                 }
                 columns.pop(); // remove last empty column
                 if columns.is_empty() {
-                    None
+                    Err(FontError::ZeroSize)
                 } else {
-                    let scaling = {
-                        let scaling = font_height.round();
+                    let scaling = |factor: f32| {
+                        let scaling = (font_height * factor).round();
                         let scaling = if scaling.is_finite() && scaling > 1. {
                             scaling
                         } else {
@@ -174,19 +211,21 @@ impl BitMapText {
                         };
                         scaling as usize
                     };
-                    let width = columns.len() * scaling;
-                    let height = 8 * scaling;
+                    let scaling_x = scaling(horizontal_scale.max(1.));
+                    let scaling_y = scaling(1.);
+                    let width = columns.len() * scaling_x;
+                    let height = 8 * scaling_y;
                     let mut data = Vec::new();
-                    for y in 0..(8 * scaling) {
+                    for y in 0..height {
                         for x in 0..width {
-                            let x = x / scaling;
-                            let y = y / scaling;
+                            let x = x / scaling_x;
+                            let y = y / scaling_y;
                             let c = columns[x][y];
                             let c = if c { 255 } else { 0 };
                             data.push(c);
                         }
                     }
-                    Some(Self {
+                    Ok(Self {
                         data,
                         width: width as i32,
                         height: height as i32,
@@ -207,3 +246,126 @@ impl BitMapText {
         }
     }
 }
+
+/// Direction of a character: Some(true) for right-to-left, Some(false) for left-to-right, None for neutral (e.g. spaces, punctuation)
+fn direction(c: char) -> Option<bool> {
+    if matches!(c as u32, 0x0590..=0x08FF | 0xFB1D..=0xFDFF | 0xFE70..=0xFEFF) {
+        Some(true)
+    } else if c.is_alphanumeric() {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// Reorder characters from logical to visual order. This is a simplified bidi algorithm:
+/// Neutral characters take the direction of their surrounding, if both sides agree, otherwise the paragraph direction.
+/// Right-to-left runs are reversed, and for a right-to-left paragraph, the order of the runs is reversed
+fn visual_order(text: &str, rtl: bool) -> String {
+    let chars = text.chars().collect::<Vec<_>>();
+    let strong = chars.iter().map(|c| direction(*c)).collect::<Vec<_>>();
+    if !rtl && strong.iter().all(|x| *x != Some(true)) {
+        return text.to_string();
+    }
+    let mut runs: Vec<(bool, Vec<char>)> = Vec::new();
+    for (i, c) in chars.into_iter().enumerate() {
+        let direction = strong[i].unwrap_or_else(|| {
+            let before = strong[..i].iter().rev().find_map(|x| *x).unwrap_or(rtl);
+            let after = strong[i + 1..].iter().find_map(|x| *x).unwrap_or(rtl);
+            if before == after {
+                before
+            } else {
+                rtl
+            }
+        });
+        match runs.last_mut() {
+            Some((d, run)) if *d == direction => run.push(c),
+            _ => runs.push((direction, vec![c])),
+        }
+    }
+    for (direction, run) in &mut runs {
+        if *direction {
+            run.reverse();
+        }
+    }
+    if rtl {
+        runs.reverse();
+    }
+    runs.into_iter().flat_map(|(_, run)| run).collect()
+}
+
+#[test]
+fn visual_order_test() {
+    // left-to-right text is unaffected
+    assert_eq!(visual_order("1|2 ab", false), "1|2 ab");
+    assert_eq!(visual_order("1|2", true), "1|2");
+    // right-to-left runs are reversed
+    assert_eq!(visual_order("ab אבג cd", false), "ab גבא cd");
+    assert_eq!(visual_order("אב גד", false), "דג בא");
+    // right-to-left paragraph
+    assert_eq!(visual_order("אבג 12", true), "12 גבא");
+    assert_eq!(visual_order("ab אבג", true), "גבא ab");
+}
+
+#[test]
+fn font_error_test() {
+    for font in [Font::EguiMonospace, Font::Font8x8] {
+        let options = FontOptions {
+            font,
+            font_height: 12.,
+            ..Default::default()
+        };
+        assert!(matches!(options.render(""), Err(FontError::EmptyText)));
+        assert!(options.render("1|2").is_ok());
+        assert!(options.render_opt("1|2").is_some());
+    }
+    let options = FontOptions {
+        font: Font::Font8x8,
+        ..Default::default()
+    };
+    assert!(matches!(options.render("  "), Err(FontError::ZeroSize)));
+}
+
+#[test]
+fn font8x8_horizontal_scale_test() {
+    let render = |horizontal_scale| {
+        FontOptions {
+            font: Font::Font8x8,
+            font_height: 3.,
+            horizontal_scale,
+            ..Default::default()
+        }
+        .render("-")
+        .unwrap()
+    };
+    let square = render(1.);
+    assert_eq!(square.height, 24);
+    let wide = render(2.);
+    assert_eq!((wide.width, wide.height), (2 * square.width, 24));
+    // each pixel of the glyph is stretched, not the glyph padded
+    let row = |bitmap: &BitMapText, y: i32| {
+        (0..bitmap.width)
+            .filter(|x| bitmap.fetch(*x, y) == Some(255))
+            .count()
+    };
+    assert!((0..24).all(|y| row(&wide, y) == 2 * row(&square, y)));
+    // values below 1 are treated as 1
+    assert!(render(0.) == square);
+    assert!(render(0.5) == square);
+}
+
+#[test]
+fn font_options_serde_default_test() {
+    // options saved before 'background_opacity' and 'rtl' existed can still be loaded
+    let mut json = serde_json::to_value(FontOptions {
+        background_opacity: 0.5,
+        rtl: true,
+        ..Default::default()
+    })
+    .unwrap();
+    json.as_object_mut().unwrap().remove("background_opacity");
+    json.as_object_mut().unwrap().remove("rtl");
+    let options: FontOptions = serde_json::from_value(json).unwrap();
+    assert_eq!(options.background_opacity, 0.);
+    assert!(!options.rtl);
+}
@@ -18,16 +18,18 @@ mod bitmap_widget_single;
 pub mod colors;
 mod font;
 mod multimap;
-pub use bitmap_data::HeatmapData;
+pub use bitmap_data::{BitmapData, ClampMask, Clamped, HeatmapData, ScaleMode};
 
 /// Some font-related types
-pub use font::{BitMapText, Font, FontOptions};
+pub use font::{BitMapText, Font, FontError, FontOptions};
 /// Color type: egui::Color32
 pub type Color = egui::Color32;
 pub use bitmap_widget_multi::{
-    ColorWithThickness, CoordinatePoint, CoordinateRect, Data, Event, MultiBitmapWidget,
-    MultiBitmapWidgetSettings, MultiMapPosition, Overlay, ShowState,
+    ColorWithThickness, ColorbarFormatter, ColorbarLabels, ColorbarPlacement, CoordinatePoint,
+    CoordinateRect, Corner, Data, Downsampling, Event, ExtremaMarkers, GridLines, Legend,
+    Localization, MultiBitmapWidget, MultiBitmapWidgetSettings, MultiMapPosition, NavigationConfig,
+    Overlay, OverlayBuilder, PanBounds, RelativeThickness, RenderProblem, ScaleBar, SelectionStyle,
+    ShowState, TextCompositing, Upsampling,
 };
 
 pub use bitmap_widget_single::{BitmapWidget, MapPosition, ShowStateSingle};
-
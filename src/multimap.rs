@@ -1,37 +1,137 @@
 mod gamma_multiplyable;
 pub use gamma_multiplyable::{BitMapDrawable, GammyMultiplyable};
 
-pub use crate::font::{BitMapText, Font, FontOptions};
+pub use crate::font::{BitMapText, FontOptions};
+#[derive(Clone)]
 pub enum KeyBoardDirection {
     Up,
     Down,
     Left,
     Right,
 }
+/// Number of points added on each side of the selection by 'zoom_to_selection'
+const SELECTION_ZOOM_MARGIN: i32 = 1;
+/// Minimal number of points shown in each direction by 'zoom_to_selection'
+const SELECTION_ZOOM_MIN_EXTENT: i32 = 5;
+/// Maximal number of selection snapshots kept for undo
+const SELECTION_HISTORY_DEPTH: usize = 100;
+/// Add to the accumulator and take out the whole steps
+fn accumulate(accumulator: &mut f32, steps: f32) -> i32 {
+    *accumulator += steps;
+    let whole = accumulator.trunc();
+    *accumulator -= whole;
+    whole as i32
+}
 #[derive(serde::Deserialize, serde::Serialize, Default)]
 pub(crate) struct MultimapState<Key: Eq + std::hash::Hash> {
     pub to_plot: std::collections::HashMap<Key, bool>,
     pub selected: std::collections::HashSet<CoordinatePoint>,
     pub shown_rectangle: Option<ShowRect>,
+    #[serde(default = "std::collections::HashSet::new")]
+    pub pinned: std::collections::HashSet<Key>,
+    #[serde(skip)]
+    selection_undo: Vec<std::collections::HashSet<CoordinatePoint>>,
+    #[serde(skip)]
+    selection_redo: Vec<std::collections::HashSet<CoordinatePoint>>,
+    #[serde(skip)]
+    scroll_accumulator: f32,
+    #[serde(skip)]
+    pan_accumulator: f32,
 }
 
 impl<Key: std::hash::Hash + Eq> MultimapState<Key> {
     fn to_plot(&self, key: &Key) -> bool {
         self.to_plot.get(key).cloned().unwrap_or(true)
     }
-    pub(crate) fn currently_showing(&self) -> Option<CoordinateRect> {
-        if let Some(ShowRect {
-            left_top,
-            right_bottom,
-        }) = &self.shown_rectangle
-        {
-            Some(CoordinateRect {
-                left_top: left_top - &CoordinatePoint { x: 0, y: 0 },
-                right_bottom: right_bottom - &CoordinatePoint { x: 0, y: 0 },
-            })
+    /// Store the current selection, so that the next change can be undone
+    pub(crate) fn push_selection_history(&mut self) {
+        if self.selection_undo.last() != Some(&self.selected) {
+            self.selection_undo.push(self.selected.clone());
+            if self.selection_undo.len() > SELECTION_HISTORY_DEPTH {
+                self.selection_undo.remove(0);
+            }
+        }
+        self.selection_redo.clear();
+    }
+    pub(crate) fn undo_selection(&mut self) -> bool {
+        if let Some(previous) = self.selection_undo.pop() {
+            let current = std::mem::replace(&mut self.selected, previous);
+            self.selection_redo.push(current);
+            true
         } else {
-            None
+            false
+        }
+    }
+    pub(crate) fn redo_selection(&mut self) -> bool {
+        if let Some(next) = self.selection_redo.pop() {
+            let current = std::mem::replace(&mut self.selected, next);
+            self.selection_undo.push(current);
+            true
+        } else {
+            false
+        }
+    }
+    /// Add a (fractional) number of zoom steps, e.g. from trackpads. Returns the whole steps to apply,
+    /// i.e. a step is taken once the total crosses a whole step. The remainder is kept until 'reset_scroll'
+    pub(crate) fn accumulate_scroll(&mut self, steps: f32) -> i32 {
+        self.pan_accumulator = 0.;
+        accumulate(&mut self.scroll_accumulator, steps)
+    }
+    /// Add a (fractional) number of points to pan horizontally, like 'accumulate_scroll' does for zoom steps
+    pub(crate) fn accumulate_pan(&mut self, points: f32) -> i32 {
+        self.scroll_accumulator = 0.;
+        accumulate(&mut self.pan_accumulator, points)
+    }
+    /// Drop fractional zoom and pan steps, e.g. if the scroll gesture ended
+    pub(crate) fn reset_scroll(&mut self) {
+        self.scroll_accumulator = 0.;
+        self.pan_accumulator = 0.;
+    }
+    /// Show the given rectangle. Returns false (and changes nothing) if it does not contain any point
+    pub(crate) fn set_shown(&mut self, rect: &CoordinateRect) -> bool {
+        if rect.left_top.x < rect.right_bottom.x && rect.left_top.y < rect.right_bottom.y {
+            self.shown_rectangle = Some(rect.into());
+            true
+        } else {
+            false
+        }
+    }
+    /// Show the bounding box of the selection, expanded by a margin and to a minimal extent.
+    /// Returns false (and changes nothing) if nothing is selected
+    pub(crate) fn zoom_to_selection(&mut self) -> bool {
+        let mut points = self.selected.iter();
+        let first = match points.next() {
+            Some(first) => first,
+            None => return false,
+        };
+        let (mut left, mut top, mut right, mut bottom) = (first.x, first.y, first.x, first.y);
+        for CoordinatePoint { x, y } in points {
+            left = left.min(*x);
+            top = top.min(*y);
+            right = right.max(*x);
+            bottom = bottom.max(*y);
         }
+        // right-bottom points behind the last selected point
+        let expand = |start: i32, end: i32| {
+            let (start, end) = (
+                start - SELECTION_ZOOM_MARGIN,
+                end + 1 + SELECTION_ZOOM_MARGIN,
+            );
+            let missing = (SELECTION_ZOOM_MIN_EXTENT - (end - start)).max(0);
+            (start - missing / 2, end + missing - missing / 2)
+        };
+        let (left, right) = expand(left, right);
+        let (top, bottom) = expand(top, bottom);
+        self.set_shown(&CoordinateRect {
+            left_top: CoordinatePoint { x: left, y: top },
+            right_bottom: CoordinatePoint {
+                x: right,
+                y: bottom,
+            },
+        })
+    }
+    pub(crate) fn currently_showing(&self) -> Option<CoordinateRect> {
+        self.shown_rectangle.as_ref().map(CoordinateRect::from)
     }
 }
 /// This is a point, using the user-given coordinate system
@@ -59,17 +159,143 @@ pub struct MultiMapPoint {
     pub y: usize,
 }
 
-#[derive(Default, Clone, serde::Deserialize, serde::Serialize)]
+#[derive(Default, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 struct ShowPoint {
     x: i32,
     y: i32,
 }
-#[derive(Default, Clone, serde::Deserialize, serde::Serialize)]
+#[derive(Default, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 pub(crate) struct ShowRect {
     left_top: ShowPoint,
     // this is right below of the last point, similiar to that an array length points "behind" the array
     right_bottom: ShowPoint,
 }
+impl From<&CoordinateRect> for ShowRect {
+    fn from(
+        CoordinateRect {
+            left_top,
+            right_bottom,
+        }: &CoordinateRect,
+    ) -> Self {
+        Self {
+            left_top: ShowPoint {
+                x: left_top.x,
+                y: left_top.y,
+            },
+            right_bottom: ShowPoint {
+                x: right_bottom.x,
+                y: right_bottom.y,
+            },
+        }
+    }
+}
+impl From<&ShowPoint> for CoordinatePoint {
+    fn from(ShowPoint { x, y }: &ShowPoint) -> Self {
+        Self { x: *x, y: *y }
+    }
+}
+impl From<&ShowRect> for CoordinateRect {
+    fn from(
+        ShowRect {
+            left_top,
+            right_bottom,
+        }: &ShowRect,
+    ) -> Self {
+        Self {
+            left_top: left_top.into(),
+            right_bottom: right_bottom.into(),
+        }
+    }
+}
+impl ShowRect {
+    fn extent(&self) -> (i32, i32) {
+        (
+            self.right_bottom.x - self.left_top.x,
+            self.right_bottom.y - self.left_top.y,
+        )
+    }
+    /// Ensure that at least one data point is shown in each direction, keeping the left-top corner fixed
+    fn normalize(&mut self) {
+        let (dx, dy) = self.extent();
+        if dx < 1 {
+            self.right_bottom.x = self.left_top.x + 1;
+        }
+        if dy < 1 {
+            self.right_bottom.y = self.left_top.y + 1;
+        }
+    }
+    /// Move this rectangle (keeping its size) as little as possible, such that it shows as much of the bounds as possible.
+    /// So if it is larger than the bounds, the bounds are fully contained. Otherwise, it is fully inside the bounds
+    fn snap_into(&mut self, bounds: &ShowRect) {
+        fn snap(start: i32, extent: i32, bounds_start: i32, bounds_end: i32) -> i32 {
+            let other = bounds_end - extent;
+            start.clamp(bounds_start.min(other), bounds_start.max(other))
+        }
+        let (dx, dy) = self.extent();
+        self.left_top.x = snap(
+            self.left_top.x,
+            dx,
+            bounds.left_top.x,
+            bounds.right_bottom.x,
+        );
+        self.left_top.y = snap(
+            self.left_top.y,
+            dy,
+            bounds.left_top.y,
+            bounds.right_bottom.y,
+        );
+        self.right_bottom.x = self.left_top.x + dx;
+        self.right_bottom.y = self.left_top.y + dy;
+    }
+    /// Move this rectangle (keeping its size) as little as possible, such that it overlaps the bounds by
+    /// at least the given fraction of the smaller extent per direction (and by at least one point)
+    fn keep_overlap(&mut self, bounds: &ShowRect, fraction: f32) {
+        fn keep(start: i32, extent: i32, bounds_start: i32, bounds_end: i32, fraction: f32) -> i32 {
+            let smaller = extent.min(bounds_end - bounds_start);
+            let overlap = ((smaller as f32 * fraction).ceil() as i32).clamp(1, smaller.max(1));
+            start.clamp(bounds_start + overlap - extent, bounds_end - overlap)
+        }
+        let fraction = if fraction.is_finite() {
+            fraction.clamp(0., 1.)
+        } else {
+            0.
+        };
+        let (dx, dy) = self.extent();
+        self.left_top.x = keep(
+            self.left_top.x,
+            dx,
+            bounds.left_top.x,
+            bounds.right_bottom.x,
+            fraction,
+        );
+        self.left_top.y = keep(
+            self.left_top.y,
+            dy,
+            bounds.left_top.y,
+            bounds.right_bottom.y,
+            fraction,
+        );
+        self.right_bottom.x = self.left_top.x + dx;
+        self.right_bottom.y = self.left_top.y + dy;
+    }
+    /// Interpolate between this and the target rectangle, ratio is in [0.0, 1.0]
+    pub(crate) fn interpolate(&self, target: &ShowRect, ratio: f32) -> ShowRect {
+        fn interpolate(start: i32, end: i32, ratio: f32) -> i32 {
+            start + ((end - start) as f32 * ratio).round() as i32
+        }
+        let ratio = ratio.clamp(0., 1.);
+        ShowRect {
+            left_top: ShowPoint {
+                x: interpolate(self.left_top.x, target.left_top.x, ratio),
+                y: interpolate(self.left_top.y, target.left_top.y, ratio),
+            },
+            right_bottom: ShowPoint {
+                x: interpolate(self.right_bottom.x, target.right_bottom.x, ratio),
+                y: interpolate(self.right_bottom.y, target.right_bottom.y, ratio),
+            },
+        }
+    }
+}
 
 /// This is a rectangle in the user-given coordinate system.
 #[derive(Debug, PartialEq)]
@@ -80,6 +306,13 @@ pub struct CoordinateRect {
     pub right_bottom: CoordinatePoint,
 }
 impl CoordinateRect {
+    /// Check if the point is inside of this rectangle
+    pub fn contains(&self, point: &CoordinatePoint) -> bool {
+        self.left_top.x <= point.x
+            && point.x < self.right_bottom.x
+            && self.left_top.y <= point.y
+            && point.y < self.right_bottom.y
+    }
     fn delta(&self) -> CoordinateVec {
         &self.right_bottom - &self.left_top
     }
@@ -129,14 +362,115 @@ struct RenderPoint {
     is_boundary: bool,
 }
 
-/// Overlay text, which is shown once user zooms in enough
+/// Overlay text, which is shown once user zooms in enough.
+/// When serialized, texts are stored as strings and rendered again on deserialization
+#[derive(serde::Deserialize)]
+#[serde(try_from = "OverlayRepr")]
 pub struct Overlay {
     font: FontOptions,
+    title_font: FontOptions,
+    coordinate_font: FontOptions,
     overlay_indices: std::collections::HashMap<CoordinatePoint, usize>,
     overlay_bitmaps: Vec<BitMapText>,
-    show_coordinates: bool,
+    // the text of each bitmap, None if the bitmap was given directly
+    overlay_strings: Vec<Option<String>>,
+    coordinate_corners: Vec<Corner>,
+    title: String,
+}
+/// Serialized form of an overlay
+#[derive(serde::Deserialize, serde::Serialize)]
+struct OverlayRepr {
+    font: FontOptions,
+    title_font: FontOptions,
+    coordinate_font: FontOptions,
+    overlay_indices: Vec<(CoordinatePoint, usize)>,
+    overlays: Vec<OverlaySource>,
+    coordinate_corners: Vec<Corner>,
     title: String,
 }
+#[derive(serde::Deserialize, serde::Serialize)]
+enum OverlaySource {
+    Text(String),
+    Bitmap(BitMapText),
+}
+impl From<&Overlay> for OverlayRepr {
+    fn from(overlay: &Overlay) -> Self {
+        let mut overlay_indices = overlay
+            .overlay_indices
+            .iter()
+            .map(|(k, i)| (k.clone(), *i))
+            .collect::<Vec<_>>();
+        overlay_indices.sort();
+        Self {
+            font: overlay.font.clone(),
+            title_font: overlay.title_font.clone(),
+            coordinate_font: overlay.coordinate_font.clone(),
+            overlay_indices,
+            overlays: overlay
+                .overlay_bitmaps
+                .iter()
+                .zip(overlay.overlay_strings.iter())
+                .map(|(bitmap, text)| match text {
+                    Some(text) => OverlaySource::Text(text.clone()),
+                    None => OverlaySource::Bitmap(bitmap.clone()),
+                })
+                .collect(),
+            coordinate_corners: overlay.coordinate_corners.clone(),
+            title: overlay.title.clone(),
+        }
+    }
+}
+impl TryFrom<OverlayRepr> for Overlay {
+    type Error = String;
+
+    fn try_from(
+        OverlayRepr {
+            font,
+            title_font,
+            coordinate_font,
+            overlay_indices,
+            overlays,
+            coordinate_corners,
+            title,
+        }: OverlayRepr,
+    ) -> Result<Self, Self::Error> {
+        if let Some((_, index)) = overlay_indices.iter().find(|(_, i)| *i >= overlays.len()) {
+            return Err(format!("Overlay index {index} is out of range"));
+        }
+        let mut overlay_bitmaps = Vec::with_capacity(overlays.len());
+        let mut overlay_strings = Vec::with_capacity(overlays.len());
+        for source in overlays {
+            match source {
+                OverlaySource::Text(text) => {
+                    let bitmap = font
+                        .render(&text)
+                        .map_err(|e| format!("Failed to render overlay text '{text}': {e:?}"))?;
+                    overlay_bitmaps.push(bitmap);
+                    overlay_strings.push(Some(text));
+                }
+                OverlaySource::Bitmap(bitmap) => {
+                    overlay_bitmaps.push(bitmap);
+                    overlay_strings.push(None);
+                }
+            }
+        }
+        Ok(Self {
+            font,
+            title_font,
+            coordinate_font,
+            overlay_indices: overlay_indices.into_iter().collect(),
+            overlay_bitmaps,
+            overlay_strings,
+            coordinate_corners,
+            title,
+        })
+    }
+}
+impl serde::Serialize for Overlay {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        OverlayRepr::from(self).serialize(serializer)
+    }
+}
 impl Overlay {
     /// Constructor
     pub fn new(
@@ -145,33 +479,72 @@ impl Overlay {
         overlay_text: std::collections::HashMap<CoordinatePoint, String>,
         title: &str,
     ) -> Option<Self> {
-        //let title = font.render(title)?;
+        overlay_text
+            .into_iter()
+            .fold(
+                Self::builder(font)
+                    .show_coordinates(show_coordinates)
+                    .title(title),
+                |builder, (k, s)| builder.text(k, &s),
+            )
+            .build()
+    }
+    /// Build an overlay step by step. By default, there is no title and no texts, and coordinates are shown
+    pub fn builder(font: FontOptions) -> OverlayBuilder {
+        OverlayBuilder {
+            font,
+            coordinate_corners: Corner::ALL.to_vec(),
+            title: String::new(),
+            overlay_indices: Default::default(),
+            overlay_bitmaps: Default::default(),
+            overlay_strings: Default::default(),
+            failed: Default::default(),
+        }
+    }
+    /// Constructor using given bitmaps (e.g. icons) instead of rendering texts.
+    /// The font is used for title and coordinates
+    pub fn with_bitmaps(
+        font: FontOptions,
+        show_coordinates: bool,
+        overlay_bitmaps: std::collections::HashMap<CoordinatePoint, BitMapText>,
+        title: &str,
+    ) -> Self {
         let mut overlay_indices = std::collections::HashMap::default();
-        let mut overlay_bitmaps = Vec::default();
-        let mut overlay_strings = Vec::default();
-        for (k, s) in overlay_text {
-            let index = if let Some(index) = overlay_strings.iter().position(|x| x == &s) {
+        let mut bitmaps = Vec::<BitMapText>::default();
+        for (k, bitmap) in overlay_bitmaps {
+            let index = if let Some(index) = bitmaps.iter().position(|x| x == &bitmap) {
                 index
             } else {
-                let bitmap = font.render(&s)?;
-                if let Some(index) = overlay_bitmaps.iter().position(|x| x == &bitmap) {
-                    index
-                } else {
-                    let index = overlay_bitmaps.len();
-                    overlay_bitmaps.push(bitmap);
-                    overlay_strings.push(s);
-                    index
-                }
+                bitmaps.push(bitmap);
+                bitmaps.len() - 1
             };
             overlay_indices.insert(k, index);
         }
-        Some(Self {
+        Self {
+            title_font: font.clone(),
+            coordinate_font: font.clone(),
             font,
             overlay_indices,
-            overlay_bitmaps,
-            show_coordinates,
+            overlay_strings: vec![None; bitmaps.len()],
+            overlay_bitmaps: bitmaps,
+            coordinate_corners: all_corners_if(show_coordinates),
             title: title.to_string(),
-        })
+        }
+    }
+    /// Show the coordinates only in the given corners. Use an empty slice to hide them
+    pub fn with_coordinate_corners(mut self, corners: &[Corner]) -> Self {
+        self.coordinate_corners = corners.to_vec();
+        self
+    }
+    /// Use a different font for the title. By default, the font of the overlay texts is used
+    pub fn with_title_font(mut self, font: FontOptions) -> Self {
+        self.title_font = font;
+        self
+    }
+    /// Use a different font for the coordinates in the corners and the scale bar. By default, the font of the overlay texts is used
+    pub fn with_coordinate_font(mut self, font: FontOptions) -> Self {
+        self.coordinate_font = font;
+        self
     }
     /// Create an exampleary overlay
     pub fn example(first_coordinate: &CoordinatePoint) -> Self {
@@ -181,7 +554,10 @@ impl Overlay {
             FontOptions {
                 font: crate::Font::EguiMonospace,
                 background_is_transparent: true,
+                background_opacity: 0.,
                 font_height: 18.,
+                rtl: false,
+                horizontal_scale: 1.,
             },
             true,
             overlay,
@@ -190,13 +566,110 @@ impl Overlay {
         .expect("Failed to generate example")
     }
 
+    /// Overlay bitmaps sorted by row and then by column, so that rendering is deterministic.
+    /// Note that each bitmap is only drawn if it fits into its cell, so bitmaps never overlap
     fn get_overlays(&self) -> impl Iterator<Item = (&CoordinatePoint, &BitMapText)> {
-        self.overlay_indices
+        let mut overlays = self
+            .overlay_indices
             .iter()
             .map(|(k, i)| (k, &self.overlay_bitmaps[*i]))
+            .collect::<Vec<_>>();
+        overlays.sort_by_key(|(CoordinatePoint { x, y }, _)| (*y, *x));
+        overlays.into_iter()
+    }
+}
+/// Builder for an overlay, see 'Overlay::builder'
+pub struct OverlayBuilder {
+    font: FontOptions,
+    coordinate_corners: Vec<Corner>,
+    title: String,
+    overlay_indices: std::collections::HashMap<CoordinatePoint, usize>,
+    overlay_bitmaps: Vec<BitMapText>,
+    overlay_strings: Vec<String>,
+    failed: Vec<CoordinatePoint>,
+}
+impl OverlayBuilder {
+    /// Set the title. Use an empty string for no title
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = title.to_string();
+        self
+    }
+    /// Shall the coordinates be shown in the corners?
+    pub fn show_coordinates(mut self, show_coordinates: bool) -> Self {
+        self.coordinate_corners = all_corners_if(show_coordinates);
+        self
+    }
+    /// Show the coordinates only in the given corners. Use an empty slice to hide them
+    pub fn coordinate_corners(mut self, corners: &[Corner]) -> Self {
+        self.coordinate_corners = corners.to_vec();
+        self
+    }
+    /// Add a text at the given point, replacing any previous text at this point.
+    /// The text is rendered immediately. If this fails, the point is listed in 'failed'
+    pub fn text(mut self, point: CoordinatePoint, text: &str) -> Self {
+        let index = if let Some(index) = self.overlay_strings.iter().position(|x| x == text) {
+            index
+        } else if let Some(bitmap) = self.font.render_opt(text) {
+            if let Some(index) = self.overlay_bitmaps.iter().position(|x| x == &bitmap) {
+                index
+            } else {
+                self.overlay_bitmaps.push(bitmap);
+                self.overlay_strings.push(text.to_string());
+                self.overlay_bitmaps.len() - 1
+            }
+        } else {
+            self.overlay_indices.remove(&point);
+            self.failed.push(point);
+            return self;
+        };
+        self.failed.retain(|x| x != &point);
+        self.overlay_indices.insert(point, index);
+        self
+    }
+    /// Points whose text failed to render
+    pub fn failed(&self) -> &[CoordinatePoint] {
+        &self.failed
+    }
+    /// Build the overlay. Returns None if any text failed to render, see 'failed'
+    pub fn build(self) -> Option<Overlay> {
+        if self.failed.is_empty() {
+            Some(self.build_lossy())
+        } else {
+            None
+        }
+    }
+    /// Build the overlay, skipping texts which failed to render
+    pub fn build_lossy(self) -> Overlay {
+        let Self {
+            font,
+            coordinate_corners,
+            title,
+            overlay_indices,
+            overlay_bitmaps,
+            overlay_strings,
+            failed: _,
+        } = self;
+        Overlay {
+            title_font: font.clone(),
+            coordinate_font: font.clone(),
+            font,
+            overlay_indices,
+            overlay_bitmaps,
+            overlay_strings: overlay_strings.into_iter().map(Some).collect(),
+            coordinate_corners,
+            title,
+        }
+    }
+}
+fn all_corners_if(show_coordinates: bool) -> Vec<Corner> {
+    if show_coordinates {
+        Corner::ALL.to_vec()
+    } else {
+        Vec::new()
     }
 }
 /// A representation of a bitmap with overlay text
+#[derive(serde::Deserialize, serde::Serialize)]
 pub struct Data<Color> {
     /// width of bitmap in pixels
     pub width: usize,
@@ -224,6 +697,28 @@ impl<Color: Clone> Data<Color> {
         }
     }
 
+    /// All coordinates covered by this data set, row by row
+    pub fn coordinates(&self) -> impl Iterator<Item = CoordinatePoint> + '_ {
+        let CoordinatePoint { x: left, y: top } = self.first_point_coordinate;
+        (0..self.height).flat_map(move |y| {
+            (0..self.width).map(move |x| CoordinatePoint {
+                x: left + x as i32,
+                y: top + y as i32,
+            })
+        })
+    }
+    /// All coordinates covered by this data set together with their colors, row by row
+    pub fn iter(&self) -> impl Iterator<Item = (CoordinatePoint, &Color)> {
+        self.coordinates().zip(self.data.iter())
+    }
+
+    pub(crate) fn contains(&self, point: &CoordinatePoint) -> bool {
+        point.x >= self.first_point_coordinate.x
+            && point.y >= self.first_point_coordinate.y
+            && ((point.x - self.first_point_coordinate.x) as usize) < self.width
+            && ((point.y - self.first_point_coordinate.y) as usize) < self.height
+    }
+
     fn bounding_box(&self) -> CoordinateRect {
         let left_top = self.first_point_coordinate.clone();
         let right_bottom = &left_top
@@ -254,7 +749,10 @@ impl Data<egui::Color32> {
         let font = FontOptions {
             font: crate::Font::EguiMonospace,
             background_is_transparent: true,
+            background_opacity: 0.,
             font_height: 12.,
+            rtl: false,
+            horizontal_scale: 1.,
         };
         let mut overlay_text = std::collections::HashMap::default();
         overlay_text.insert(first_point_coordinate.clone(), "FP".to_string());
@@ -274,7 +772,10 @@ impl Data<egui::Color32> {
         let font = FontOptions {
             font: crate::Font::EguiMonospace,
             background_is_transparent: true,
+            background_opacity: 0.,
             font_height: 12.,
+            rtl: false,
+            horizontal_scale: 1.,
         };
         for y in 0..height {
             for x in 0..width {
@@ -309,7 +810,251 @@ impl Data<egui::Color32> {
     }
 }
 
+/// A corner of a plot
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum Corner {
+    /// Left top corner
+    LeftTop,
+    /// Right top corner
+    RightTop,
+    /// Left bottom corner
+    LeftBottom,
+    /// Right bottom corner
+    RightBottom,
+}
+impl Corner {
+    /// All four corners
+    pub const ALL: [Corner; 4] = [
+        Corner::LeftTop,
+        Corner::RightTop,
+        Corner::LeftBottom,
+        Corner::RightBottom,
+    ];
+}
+
+/// Settings for a scale bar, which shows a length in physical units
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct ScaleBar<Color> {
+    /// Corner of each plot where the scale bar is drawn
+    pub position: Corner,
+    /// Color of the bar
+    pub color: Color,
+    /// Thickness of the bar in pixels
+    pub thickness: usize,
+    /// Physical length of one data point, e.g. 0.5 for 0.5 µm per data point
+    pub units_per_point: f64,
+    /// Unit shown next to the length, e.g. "µm"
+    pub unit: String,
+}
+
+/// Settings for a legend explaining the colors of the selection boundaries and of areas without data
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct Legend {
+    /// Corner of the plot area (excluding the colorbar) where the legend is drawn
+    pub position: Corner,
+    /// Font of the labels
+    pub font: FontOptions,
+    /// Label of the boundary of selected points, e.g. "Selected". Use an empty string to skip this entry
+    pub text_selected: String,
+    /// Label of the boundary of unselected points, e.g. "Not selected". Use an empty string to skip this entry
+    pub text_unselected: String,
+    /// Label of areas without data, e.g. "No data". Use an empty string to skip this entry
+    pub text_no_data: String,
+}
+impl Legend {
+    /// Legend with english labels
+    pub fn english(position: Corner, font: FontOptions) -> Self {
+        Self {
+            position,
+            font,
+            text_selected: "Selected".to_string(),
+            text_unselected: "Not selected".to_string(),
+            text_no_data: "No data".to_string(),
+        }
+    }
+}
+
+/// Settings for grid lines, which are aligned to the user-given coordinates
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct GridLines<Color> {
+    /// Distance between two major grid lines, in data points. Lines are drawn at multiples of this
+    pub spacing: usize,
+    /// Color of major grid lines
+    pub color: Color,
+    /// Thickness of major grid lines in pixels
+    pub thickness: usize,
+    /// Number of minor grid lines between two major grid lines. Use 0 to disable minor grid lines
+    pub minor_divisions: usize,
+    /// Color of minor grid lines
+    pub minor_color: Color,
+    /// Thickness of minor grid lines in pixels
+    pub minor_thickness: usize,
+}
+/// Minimal distance of two minor grid lines in pixels. Otherwise, minor grid lines are skipped
+const MINOR_GRID_MIN_PIXELS: f64 = 4.;
+impl<Color> GridLines<Color> {
+    /// Check if a pixel is on a grid line along one axis. Returns Some(true) for major lines and Some(false) for minor lines
+    fn hit(&self, pixel: usize, pixels: usize, points: usize, first: i32) -> Option<bool> {
+        let pixels_per_point = pixels as f64 / points as f64;
+        let pixel_size = 1. / pixels_per_point;
+        // position of the left/top edge of this pixel, in data coordinates, see 'cell_edge'
+        let position = (pixel as f64 + cell_edge(first, pixels, points) as f64) * pixel_size;
+        let on_line = |step: f64, thickness: usize| {
+            (position + pixel_size * 1e-6).rem_euclid(step) < thickness as f64 * pixel_size
+        };
+        let spacing = std::cmp::max(1, self.spacing) as f64;
+        if spacing * pixels_per_point > 2. * self.thickness as f64
+            && on_line(spacing, self.thickness)
+        {
+            Some(true)
+        } else if self.minor_divisions > 0 {
+            let step = spacing / (self.minor_divisions + 1) as f64;
+            (step * pixels_per_point >= MINOR_GRID_MIN_PIXELS
+                && on_line(step, self.minor_thickness))
+            .then_some(false)
+        } else {
+            None
+        }
+    }
+}
+
+/// How texts (titles, coordinates, overlay texts, labels) are drawn onto the image
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum TextCompositing<Color> {
+    /// Add the brightness of the glyphs to the colors, which suits dark color maps
+    #[default]
+    Additive,
+    /// Blend the colors towards the given text color by the brightness of the glyphs, which keeps texts legible on light color maps
+    Over(Color),
+}
+
+/// How selected data points are highlighted
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum SelectionStyle {
+    /// Draw the boundary of selected data points. Boundaries are only shown if the data points are large enough
+    #[default]
+    Outline,
+    /// Blend the whole data point with the given opacity (0.0 to 1.0), so it is visible even at 1-2 pixels per point
+    Fill(f32),
+}
+
+/// How to choose the color of a pixel, if it covers several data points
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum Downsampling {
+    /// Show a single representative data point. This is fast, but small features may be dropped
+    #[default]
+    Nearest,
+    /// Average all data points covered by the pixel (box filter)
+    Average,
+}
+
+/// How to choose the color of a pixel, if several pixels show the same data point
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum Upsampling {
+    /// Each data point is drawn as a block of a single color
+    #[default]
+    Nearest,
+    /// Interpolate between the colors of the four surrounding data points, for a smooth appearance of continuous data.
+    /// Boundaries are only drawn around selected points
+    Bilinear,
+}
+
+/// Number format of the colorbar labels
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum ColorbarLabels {
+    /// Scientific notation, with as many significant digits (2 to 5) as fit into the colorbar
+    #[default]
+    Auto,
+    /// Scientific notation with a fixed number of significant digits
+    Scientific(usize),
+    /// Decimal notation with a fixed number of decimals
+    Decimal(usize),
+}
+impl ColorbarLabels {
+    /// Label candidates, most preferred first
+    fn candidates(&self, value: f64) -> Vec<String> {
+        fn scientific(value: f64, precision: usize) -> String {
+            let mut num = format!("{value:+3.precision$E}");
+            let exp = num.split_off(num.find('E').unwrap());
+            let (sign, exp) = if let Some(stripped) = exp.strip_prefix("E-") {
+                ('-', stripped)
+            } else {
+                ('+', &exp[1..])
+            };
+            num.push_str(&format!("E{}{:0>pad$}", sign, exp, pad = 2));
+            num
+        }
+        match self {
+            ColorbarLabels::Auto => (1..5)
+                .rev()
+                .map(|precision| scientific(value, precision))
+                .collect(),
+            ColorbarLabels::Scientific(digits) => {
+                vec![scientific(value, digits.saturating_sub(1))]
+            }
+            ColorbarLabels::Decimal(decimals) => vec![format!("{value:.decimals$}")],
+        }
+    }
+}
+
+/// Limits panning, such that part of the data stays visible
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct PanBounds {
+    /// Fraction (in [0.0, 1.0]) of the shown extent which stays covered by the data, per direction.
+    /// If the data is smaller than the shown extent, this is a fraction of the data extent instead.
+    /// At least one data point stays visible
+    pub visible_fraction: f32,
+}
+
+/// Formatter of the colorbar labels, see 'MultiBitmapWidgetSettings::colorbar_formatter'
+pub type ColorbarFormatter = Box<dyn Fn(f64) -> String + Send + Sync>;
+
+/// Pixel rectangle: [left, top, width, height]
+type PixelRect = [usize; 4];
+
+/// Side of the image where the colorbar is drawn
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum ColorbarPlacement {
+    /// Vertical colorbar right of the plots, the highest value at the top
+    #[default]
+    Right,
+    /// Vertical colorbar left of the plots, the highest value at the top
+    Left,
+    /// Horizontal colorbar above the plots, the highest value at the right
+    Top,
+    /// Horizontal colorbar below the plots, the highest value at the right
+    Bottom,
+}
+impl ColorbarPlacement {
+    fn is_horizontal(&self) -> bool {
+        matches!(self, ColorbarPlacement::Top | ColorbarPlacement::Bottom)
+    }
+}
+
+/// Thickness of the colorbar relative to the width of the widget
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct RelativeThickness {
+    /// Fraction of the widget width, e.g. 0.08
+    pub fraction: f32,
+    /// Minimal thickness in pixels
+    pub min: usize,
+    /// Maximal thickness in pixels
+    pub max: usize,
+}
+impl RelativeThickness {
+    fn thickness(&self, width: usize) -> usize {
+        let thickness = (self.fraction * width as f32).round();
+        let thickness = if thickness.is_finite() && thickness > 0. {
+            thickness as usize
+        } else {
+            0
+        };
+        thickness.clamp(self.min, self.max.max(self.min))
+    }
+}
+
 /// This types bundles a color with a size
+#[derive(serde::Deserialize, serde::Serialize)]
 pub struct ColorWithThickness<Color> {
     /// Color of this item
     pub color: Color,
@@ -325,29 +1070,81 @@ pub(crate) struct DataWithMetadata<Key, Color> {
 pub(crate) struct ShowMultiMap<Key, Color> {
     data: Vec<DataWithMetadata<Key, Color>>,
     boundary_between_data: ColorWithThickness<Color>,
-    colorbar: Option<(crate::colors::Gradient<Color>, usize, (f32, f32))>,
+    colorbar: Option<(crate::colors::Gradient<Color>, usize, (f64, f64))>,
+    colorbar_visible: bool,
     background: Color,
     boundary_unselected: ColorWithThickness<Color>,
     boundary_selected: Color,
     boundary_factor_min: usize,
+    downsampling: Downsampling,
+    scale_bar: Option<ScaleBar<Color>>,
+    grid: Option<GridLines<Color>>,
+    truncate_titles: bool,
+    colorbar_labels: ColorbarLabels,
+    colorbar_relative_thickness: Option<RelativeThickness>,
+    colorbar_gap: Option<usize>,
+    drag_dim_factor: f32,
+    drag_outline: Option<ColorWithThickness<Color>>,
+    selection_style: SelectionStyle,
+    colorbar_font: Option<FontOptions>,
+    min_font_height: f32,
+    text_compositing: TextCompositing<Color>,
+    legend: Option<Legend>,
+    colorbar_ticks: usize,
+    colorbar_placement: ColorbarPlacement,
+    colorbar_formatter: Option<ColorbarFormatter>,
+    min_shown_extent: Option<i32>,
+    max_shown_extent: Option<i32>,
+    pan_bounds: Option<PanBounds>,
+    upsampling: Upsampling,
+    colorbar_scale: crate::ScaleMode,
     drag_area: Option<((CoordinatePoint, CoordinatePoint), CoordinatePoint)>,
 }
 
+/// Reason why the widget could not be rendered (or copied), see 'ShowState::render_problem'
 #[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
 pub enum RenderProblem {
+    /// All data sets are hidden
     CountIsZero,
+    /// The widget is not wider than the colorbar (placed left or right)
     WidthSmallerThanColorBar,
+    /// The widget is not higher than the colorbar (placed at the top or bottom)
+    HeightSmallerThanColorBar,
+    /// There is no data set at all. The widget shows a 'no data' text instead
     NoData,
+    /// Copying to the clipboard failed
     ClipboardIssue(String),
 }
 
 pub(crate) struct ShowMultiMapSettings<Color> {
     pub boundary_between_data: ColorWithThickness<Color>,
-    pub colorbar: Option<(crate::colors::Gradient<Color>, usize, (f32, f32))>,
+    pub colorbar: Option<(crate::colors::Gradient<Color>, usize, (f64, f64))>,
     pub background: Color,
     pub boundary_unselected: ColorWithThickness<Color>,
     pub boundary_selected: Color,
     pub boundary_factor_min: usize,
+    pub downsampling: Downsampling,
+    pub scale_bar: Option<ScaleBar<Color>>,
+    pub grid: Option<GridLines<Color>>,
+    pub truncate_titles: bool,
+    pub colorbar_labels: ColorbarLabels,
+    pub colorbar_relative_thickness: Option<RelativeThickness>,
+    pub colorbar_gap: Option<usize>,
+    pub drag_dim_factor: f32,
+    pub drag_outline: Option<ColorWithThickness<Color>>,
+    pub selection_style: SelectionStyle,
+    pub colorbar_font: Option<FontOptions>,
+    pub min_font_height: f32,
+    pub text_compositing: TextCompositing<Color>,
+    pub legend: Option<Legend>,
+    pub colorbar_ticks: usize,
+    pub colorbar_placement: ColorbarPlacement,
+    pub colorbar_formatter: Option<ColorbarFormatter>,
+    pub min_shown_extent: Option<i32>,
+    pub max_shown_extent: Option<i32>,
+    pub pan_bounds: Option<PanBounds>,
+    pub upsampling: Upsampling,
+    pub colorbar_scale: crate::ScaleMode,
 }
 
 impl<Key: std::hash::Hash + Eq + Clone, Color: Clone + GammyMultiplyable + BitMapDrawable>
@@ -360,6 +1157,11 @@ impl<Key: std::hash::Hash + Eq + Clone, Color: Clone + GammyMultiplyable + BitMa
             selected: Default::default(),
             shown_rectangle: None,
             to_plot,
+            pinned: Default::default(),
+            selection_undo: Default::default(),
+            selection_redo: Default::default(),
+            scroll_accumulator: 0.,
+            pan_accumulator: 0.,
         }
     }
     pub(crate) fn with_settings(
@@ -373,111 +1175,378 @@ impl<Key: std::hash::Hash + Eq + Clone, Color: Clone + GammyMultiplyable + BitMa
             boundary_unselected,
             boundary_selected,
             boundary_factor_min,
+            downsampling,
+            scale_bar,
+            grid,
+            truncate_titles,
+            colorbar_labels,
+            colorbar_relative_thickness,
+            colorbar_gap,
+            drag_dim_factor,
+            drag_outline,
+            selection_style,
+            colorbar_font,
+            min_font_height,
+            text_compositing,
+            legend,
+            colorbar_ticks,
+            colorbar_placement,
+            colorbar_formatter,
+            min_shown_extent,
+            max_shown_extent,
+            pan_bounds,
+            upsampling,
+            colorbar_scale,
         } = settings;
         Self {
             data,
             boundary_between_data,
             colorbar,
+            colorbar_visible: true,
             background,
             boundary_unselected,
             boundary_selected,
             boundary_factor_min,
+            downsampling,
+            scale_bar,
+            grid,
+            truncate_titles,
+            colorbar_labels,
+            colorbar_relative_thickness,
+            colorbar_gap,
+            drag_dim_factor,
+            drag_outline,
+            selection_style,
+            colorbar_font,
+            min_font_height,
+            text_compositing,
+            legend,
+            colorbar_ticks,
+            colorbar_placement,
+            colorbar_formatter,
+            min_shown_extent,
+            max_shown_extent,
+            pan_bounds,
+            upsampling,
+            colorbar_scale,
             drag_area: Default::default(),
         }
     }
-    pub(crate) fn render(
+    /// Replace the data set with the given key. Returns false if there is no such data set
+    pub(crate) fn replace_data(&mut self, key: &Key, data: Data<Color>) -> bool {
+        if let Some(d) = self.data.iter_mut().find(|d| &d.key == key) {
+            d.data = data;
+            true
+        } else {
+            false
+        }
+    }
+    /// Gradient, thickness and limits of the colorbar, if it is shown in an image of the given width
+    fn shown_colorbar(
         &self,
         width: usize,
-        height: usize,
-        state: &mut MultimapState<Key>,
-    ) -> Result<Vec<Color>, RenderProblem> {
-        if state.shown_rectangle.is_none() {
-            if self.data.is_empty() {
-                return Err(RenderProblem::NoData);
-            } else {
-                state.shown_rectangle = Some(home_rect(&self.data, &state.to_plot));
-            }
+    ) -> Option<(&crate::colors::Gradient<Color>, usize, (f64, f64))> {
+        let (gradient, thickness, limits) =
+            self.colorbar.as_ref().filter(|_| self.colorbar_visible)?;
+        let thickness = self
+            .colorbar_relative_thickness
+            .map_or(*thickness, |relative| relative.thickness(width));
+        Some((gradient, thickness, *limits))
+    }
+    /// Marker line across the colorbar at the given value: start and end point (column, row) in pixels, may be fractional.
+    /// Returns None if the colorbar is not shown in an image of the given size
+    pub(crate) fn colorbar_position(&self, value: f64, size: [usize; 2]) -> Option<[[f32; 2]; 2]> {
+        let (_, _, limits) = self.shown_colorbar(size[0])?;
+        let (lower, upper) = self.scaled_colorbar_limits(limits);
+        let value = self.colorbar_scale.apply_f64(value);
+        let fraction = crate::colors::value_to_fraction(lower, upper, value);
+        if fraction.is_nan() {
+            return None;
         }
-        let shown_rectangle = state.shown_rectangle.as_ref().unwrap();
-
-        let mut data_sets = self
-            .data
-            .iter()
-            .filter_map(|d| {
-                if state.to_plot(&d.key) {
-                    Some(&d.data)
-                } else {
-                    None
-                }
-            })
-            .rev()
-            .collect::<Vec<_>>();
-        let count = data_sets.len();
-
-        if count == 0 {
-            return Err(RenderProblem::CountIsZero);
+        let (_, [left, top, width, height]) = self.split_colorbar(size).ok()?.1?;
+        let (left, top, right, bottom) = (
+            left as f32,
+            top as f32,
+            (left + width) as f32,
+            (top + height) as f32,
+        );
+        Some(if self.colorbar_placement.is_horizontal() {
+            let x = left + fraction * width as f32;
+            [[x, top], [x, bottom]]
+        } else {
+            let y = top + (1. - fraction) * height as f32;
+            [[left, y], [right, y]]
+        })
+    }
+    pub(crate) fn set_colorbar(
+        &mut self,
+        colorbar: Option<(crate::colors::Gradient<Color>, usize, (f64, f64))>,
+    ) {
+        self.colorbar = colorbar;
+    }
+    /// Colorbar limits (given in data units) in scaled units, see 'colorbar_scale'
+    fn scaled_colorbar_limits(&self, (lower, upper): (f64, f64)) -> (f64, f64) {
+        (
+            self.colorbar_scale.apply_f64(lower),
+            self.colorbar_scale.apply_f64(upper),
+        )
+    }
+    pub(crate) fn set_colorbar_limits(&mut self, lower: f64, upper: f64) -> bool {
+        let (scaled_lower, scaled_upper) = self.scaled_colorbar_limits((lower, upper));
+        if !(scaled_lower.is_finite() && scaled_upper.is_finite() && scaled_lower < scaled_upper) {
+            return false;
         }
-        let (data_columns, data_rows) = compute_columns_rows(count);
-        assert!(data_columns > 0);
-        assert!(data_rows > 0);
-        let (width_per_data, height_per_data) = {
-            let cb_thickness = self
-                .colorbar
-                .as_ref()
-                .map(|(_, thickness, _)| thickness + self.boundary_between_data.thickness)
-                .unwrap_or(0);
-            let width_without_colorbar = if width >= cb_thickness {
-                width - cb_thickness
-            } else {
-                return Err(RenderProblem::WidthSmallerThanColorBar);
-            };
-            let width_without_colorbar_and_boundaries =
-                width_without_colorbar - self.boundary_between_data.thickness * (data_columns - 1);
-            let width_per_data = width_without_colorbar_and_boundaries / data_columns;
-            let height_without_colorbar_and_boundaries =
-                height - self.boundary_between_data.thickness * (data_rows - 1);
-            let height_per_data = height_without_colorbar_and_boundaries / data_rows;
-            (width_per_data, height_per_data)
+        if let Some((_, _, limits)) = &mut self.colorbar {
+            *limits = (lower, upper);
+            true
+        } else {
+            false
+        }
+    }
+    pub(crate) fn set_colorbar_visible(&mut self, visible: bool) {
+        self.colorbar_visible = visible;
+    }
+    pub(crate) fn colorbar_visible(&self) -> bool {
+        self.colorbar_visible
+    }
+    /// Width of the separator between plots and colorbar
+    fn colorbar_gap(&self) -> usize {
+        self.colorbar_gap
+            .unwrap_or(self.boundary_between_data.thickness)
+    }
+    /// Width of the colorbar including the separator to the plots, measured across the colorbar
+    fn colorbar_extent(&self, width: usize) -> usize {
+        self.shown_colorbar(width)
+            .map(|(_, thickness, _)| thickness + self.colorbar_gap())
+            .unwrap_or(0)
+    }
+    /// Split an image of the given size into the plot area and (if shown) the colorbar.
+    /// Returns the pixel rectangles [left, top, width, height] of the plot area, and of the separator and the colorbar
+    fn split_colorbar(
+        &self,
+        [width, height]: [usize; 2],
+    ) -> Result<(PixelRect, Option<(PixelRect, PixelRect)>), RenderProblem> {
+        let thickness = match self.shown_colorbar(width) {
+            Some((_, thickness, _)) => thickness,
+            None => return Ok(([0, 0, width, height], None)),
         };
-        let plot_width = data_columns * width_per_data
-            + self.boundary_between_data.thickness * (data_columns - 1);
+        let gap = self.colorbar_gap();
+        let extent = thickness + gap;
+        if self.colorbar_placement.is_horizontal() && height < extent {
+            return Err(RenderProblem::HeightSmallerThanColorBar);
+        } else if !self.colorbar_placement.is_horizontal() && width < extent {
+            return Err(RenderProblem::WidthSmallerThanColorBar);
+        }
+        let (plot, separator, colorbar) = match self.colorbar_placement {
+            ColorbarPlacement::Right => (
+                [0, 0, width - extent, height],
+                [width - extent, 0, gap, height],
+                [width - thickness, 0, thickness, height],
+            ),
+            ColorbarPlacement::Left => (
+                [extent, 0, width - extent, height],
+                [thickness, 0, gap, height],
+                [0, 0, thickness, height],
+            ),
+            ColorbarPlacement::Top => (
+                [0, extent, width, height - extent],
+                [0, thickness, width, gap],
+                [0, 0, width, thickness],
+            ),
+            ColorbarPlacement::Bottom => (
+                [0, 0, width, height - extent],
+                [0, height - extent, width, gap],
+                [0, height - thickness, width, thickness],
+            ),
+        };
+        Ok((plot, Some((separator, colorbar))))
+    }
+    /// Arrangement of the given number of subplots in an image of the given size (including the colorbar):
+    /// (columns, rows, width per subplot, height per subplot)
+    fn layout(
+        &self,
+        count: usize,
+        width: usize,
+        height: usize,
+    ) -> Result<(usize, usize, usize, usize), RenderProblem> {
+        let ([_, _, width, height], _) = self.split_colorbar([width, height])?;
+        Ok(self.layout_plot_area(count, width, height))
+    }
+    /// Arrangement of the given number of subplots in a plot area (excluding the colorbar) of the given size:
+    /// (columns, rows, width per subplot, height per subplot)
+    fn layout_plot_area(
+        &self,
+        count: usize,
+        width: usize,
+        height: usize,
+    ) -> (usize, usize, usize, usize) {
+        let (data_columns, data_rows) = compute_columns_rows(count);
+        assert!(data_columns > 0);
+        assert!(data_rows > 0);
+        let width_without_boundaries =
+            width.saturating_sub(self.boundary_between_data.thickness * (data_columns - 1));
+        let width_per_data = width_without_boundaries / data_columns;
+        let height_without_boundaries =
+            height.saturating_sub(self.boundary_between_data.thickness * (data_rows - 1));
+        let height_per_data = height_without_boundaries / data_rows;
+        (data_columns, data_rows, width_per_data, height_per_data)
+    }
+    /// Height of the image such that the subplots of the given width match the aspect ratio of the data.
+    /// Returns None if nothing is shown or the width is too small
+    pub(crate) fn height_for_width(
+        &self,
+        width: usize,
+        state: &MultimapState<Key>,
+    ) -> Option<usize> {
+        let count = self.data.iter().filter(|d| state.to_plot(&d.key)).count();
+        if count == 0 {
+            return None;
+        }
+        // a horizontal colorbar needs additional height
+        let colorbar_height = if self.colorbar_placement.is_horizontal() {
+            self.colorbar_extent(width)
+        } else {
+            0
+        };
+        let (_, data_rows, width_per_data, _) = self.layout(count, width, colorbar_height).ok()?;
+        let (dx, dy) = home_rect(&self.data, &state.to_plot).extent();
+        if dx <= 0 || dy <= 0 {
+            return None;
+        }
+        let height_per_data = width_per_data * dy as usize / dx as usize;
+        Some(
+            data_rows * height_per_data
+                + self.boundary_between_data.thickness * (data_rows - 1)
+                + colorbar_height,
+        )
+    }
+    /// Pixel rectangle [left, top, width, height] of the subplot showing the given data set, if it is shown
+    pub(crate) fn subplot_rect(
+        &self,
+        key: &Key,
+        [width, height]: [usize; 2],
+        state: &MultimapState<Key>,
+    ) -> Option<[usize; 4]> {
+        let shown = self
+            .data
+            .iter()
+            .filter(|d| state.to_plot(&d.key))
+            .collect::<Vec<_>>();
+        let index = shown.iter().position(|d| &d.key == key)?;
+        let ([left, top, width, height], _) = self.split_colorbar([width, height]).ok()?;
+        let (data_columns, _, width_per_data, height_per_data) =
+            self.layout_plot_area(shown.len(), width, height);
+        let thickness = self.boundary_between_data.thickness;
+        Some([
+            left + (index % data_columns) * (width_per_data + thickness),
+            top + (index / data_columns) * (height_per_data + thickness),
+            width_per_data,
+            height_per_data,
+        ])
+    }
+    /// All points of the given row within any shown data set, sorted by column
+    pub(crate) fn row_points(&self, y: i32, state: &MultimapState<Key>) -> Vec<CoordinatePoint> {
+        let mut points = self
+            .data
+            .iter()
+            .filter(|d| state.to_plot(&d.key))
+            .flat_map(|d| d.data.coordinates().filter(|p| p.y == y))
+            .collect::<Vec<_>>();
+        points.sort();
+        points.dedup();
+        points
+    }
+    /// All points of the given column within any shown data set, sorted by row
+    pub(crate) fn column_points(&self, x: i32, state: &MultimapState<Key>) -> Vec<CoordinatePoint> {
+        let mut points = self
+            .data
+            .iter()
+            .filter(|d| state.to_plot(&d.key))
+            .flat_map(|d| d.data.coordinates().filter(|p| p.x == x))
+            .collect::<Vec<_>>();
+        points.sort_by_key(|CoordinatePoint { x, y }| (*y, *x));
+        points.dedup();
+        points
+    }
+    /// Size in pixels of each subplot, if the shown data sets are rendered at the given size
+    pub(crate) fn plot_area_size(
+        &self,
+        [width, height]: [usize; 2],
+        state: &MultimapState<Key>,
+    ) -> Option<[usize; 2]> {
+        let count = self.data.iter().filter(|d| state.to_plot(&d.key)).count();
+        if count == 0 {
+            return None;
+        }
+        let (_, _, width_per_data, height_per_data) = self.layout(count, width, height).ok()?;
+        Some([width_per_data, height_per_data])
+    }
+    /// Image without data: background with the given text centered, if it fits
+    pub(crate) fn render_empty(&self, width: usize, height: usize, text: &str) -> Vec<Color> {
         let mut rendered = vec![self.background.clone(); width * height];
-        let render_width = width;
-        fn draw_axis_label<Color: BitMapDrawable + Clone>(
-            data: &mut [Color],
-            bitmapfont: &BitMapText,
-            x_offset: usize,
-            y_offset: usize,
-            render_width: usize,
-            background_is_transparent: bool,
-            background: &Color,
-        ) {
-            for column in 0..bitmapfont.width {
-                for row in 0..bitmapfont.height {
-                    let x = column as usize + x_offset;
-                    let y = row as usize + y_offset;
-                    let i = x + y * render_width;
-                    let c = match (background_is_transparent, bitmapfont.fetch(column, row)) {
-                        (true, None) => {
-                            /* nothing to do - but this should never occur*/
-                            continue;
-                        }
-                        (false, None) => background.clone(),
-
-                        (true, Some(gray)) => {
-                            if let Some(c) = data.get(i) {
-                                c.saturating_add(gray)
-                            } else {
-                                continue;
-                            }
-                        }
-                        (false, Some(gray)) => Color::gray(gray),
-                    };
-                    data[i] = c;
-                }
+        let font = FontOptions {
+            background_is_transparent: true,
+            font_height: 18.,
+            ..Default::default()
+        };
+        if let Some(label) = font.render_opt(text) {
+            let (w, h) = (label.width as usize, label.height as usize);
+            if w <= width && h <= height {
+                draw_axis_label(
+                    &mut rendered,
+                    &label,
+                    (width - w) / 2,
+                    (height - h) / 2,
+                    width,
+                    &font,
+                    &self.background,
+                    &self.text_compositing,
+                );
             }
         }
+        rendered
+    }
+    pub(crate) fn render(
+        &self,
+        width: usize,
+        height: usize,
+        state: &mut MultimapState<Key>,
+    ) -> Result<Vec<Color>, RenderProblem> {
+        if state.shown_rectangle.is_none() {
+            if self.data.is_empty() {
+                return Err(RenderProblem::NoData);
+            } else {
+                state.shown_rectangle = Some(home_rect(&self.data, &state.to_plot));
+            }
+        }
+        let shown_rectangle = state.shown_rectangle.as_ref().unwrap();
 
+        let mut data_sets = self
+            .data
+            .iter()
+            .filter_map(|d| {
+                if state.to_plot(&d.key) {
+                    Some(&d.data)
+                } else {
+                    None
+                }
+            })
+            .rev()
+            .collect::<Vec<_>>();
+        let count = data_sets.len();
+
+        if count == 0 {
+            return Err(RenderProblem::CountIsZero);
+        }
+        // the plots are rendered into the plot area, which is copied into the image afterwards
+        let (plot_area, colorbar_area) = self.split_colorbar([width, height])?;
+        let [_, _, area_width, area_height] = plot_area;
+        let (data_columns, data_rows, width_per_data, height_per_data) =
+            self.layout_plot_area(count, area_width, area_height);
+        let plot_width = data_columns * width_per_data
+            + self.boundary_between_data.thickness * (data_columns - 1);
+        let mut rendered = vec![self.background.clone(); area_width * area_height];
+        let render_width = area_width;
         for data_row in 0..data_rows {
             // add boundary rows above the data to draw in this iteration
             if data_row > 0 {
@@ -486,7 +1555,8 @@ impl<Key: std::hash::Hash + Eq + Clone, Color: Clone + GammyMultiplyable + BitMa
                         + i
                         - self.boundary_between_data.thickness;
                     for column in 0..plot_width {
-                        rendered[column + row * width] = self.boundary_between_data.color.clone();
+                        rendered[column + row * render_width] =
+                            self.boundary_between_data.color.clone();
                     }
                 }
             }
@@ -500,226 +1570,125 @@ impl<Key: std::hash::Hash + Eq + Clone, Color: Clone + GammyMultiplyable + BitMa
                             let column = j + data_column
                                 * (width_per_data + self.boundary_between_data.thickness)
                                 - self.boundary_between_data.thickness;
-                            rendered[column + row * width] =
+                            rendered[column + row * render_width] =
                                 self.boundary_between_data.color.clone();
                         }
                     }
                 }
                 // render data
                 if let Some(data) = data_sets.pop() {
-                    let shown_rectangle = shown_rectangle - &CoordinatePoint { x: 0, y: 0 };
+                    let shown_rectangle = CoordinateRect::from(shown_rectangle);
                     let delta = shown_rectangle.delta();
                     let width_per_point = width_per_data / delta.x;
                     let height_per_point = height_per_data / delta.y;
-                    let overlay_offset_lt = if width_per_point > 0 && height_per_point > 0 {
-                        let boundary_thickness = if width_per_point
-                            > self.boundary_factor_min * self.boundary_unselected.thickness
-                            && height_per_point
-                                > self.boundary_factor_min * self.boundary_unselected.thickness
-                        {
-                            self.boundary_unselected.thickness
-                        } else {
-                            0
-                        };
-                        let offset_x = (width_per_data.rem_euclid(width_per_point) + 1) / 2;
-                        let offset_y = (height_per_data.rem_euclid(height_per_point) + 1) / 2;
-                        for row in 0..height_per_data {
-                            for column in 0..width_per_data {
-                                let render_point = {
-                                    let mut is_boundary = false;
-                                    let x = if column < offset_x {
-                                        if column + boundary_thickness >= offset_x {
-                                            is_boundary = true;
-                                        }
-                                        shown_rectangle.left_top.x - 1
-                                    } else {
-                                        let column = column - offset_x;
-                                        let x = column / width_per_point;
-                                        let rem = column.rem_euclid(width_per_point);
-                                        if rem < boundary_thickness
-                                            || rem + boundary_thickness >= width_per_point
-                                        {
-                                            is_boundary = true;
-                                        }
-                                        shown_rectangle.left_top.x + x as i32
-                                    };
-                                    let y = if row < offset_y {
-                                        if row + boundary_thickness >= offset_y {
-                                            is_boundary = true;
-                                        }
-                                        shown_rectangle.left_top.y - 1
-                                    } else {
-                                        let row = row - offset_y;
-                                        let y = row / height_per_point;
-                                        let rem = row.rem_euclid(height_per_point);
-                                        if rem < boundary_thickness
-                                            || rem + boundary_thickness >= height_per_point
-                                        {
-                                            is_boundary = true;
-                                        }
-                                        shown_rectangle.left_top.y + y as i32
-                                    };
-                                    RenderPoint {
-                                        coordinate: CoordinatePoint { x, y },
-                                        is_boundary,
-                                    }
-                                };
-                                self.update_color(
-                                    data,
-                                    render_point,
-                                    row,
-                                    data_row,
-                                    height_per_data,
-                                    column,
-                                    data_column,
-                                    width_per_data,
-                                    &mut rendered,
-                                    width,
-                                    state,
-                                );
+                    let boundary_thickness =
+                        self.boundary_thickness(width_per_point, height_per_point);
+                    let bilinear = self.upsampling == Upsampling::Bilinear
+                        && (width_per_data > delta.x || height_per_data > delta.y);
+                    for row in 0..height_per_data {
+                        for column in 0..width_per_data {
+                            let mut render_point = render_point(
+                                column,
+                                row,
+                                [width_per_data, height_per_data],
+                                &shown_rectangle,
+                                boundary_thickness,
+                            );
+                            if bilinear {
+                                render_point.is_boundary &=
+                                    state.selected.contains(&render_point.coordinate);
                             }
-                        }
-                        Some((offset_x, offset_y))
-                    } else if width_per_point > 0 && height_per_point == 0 {
-                        let boundary_thickness = if width_per_point
-                            > self.boundary_factor_min * self.boundary_unselected.thickness
-                        {
-                            self.boundary_unselected.thickness
-                        } else {
-                            0
-                        };
-                        let offset_x = (width_per_data.rem_euclid(width_per_point) + 1) / 2;
-                        for row in 0..height_per_data {
-                            for column in 0..width_per_data {
-                                let render_point = {
-                                    let mut is_boundary = false;
-                                    let x = if column < offset_x {
-                                        if column + boundary_thickness >= offset_x {
-                                            is_boundary = true;
-                                        }
-                                        shown_rectangle.left_top.x - 1
-                                    } else {
-                                        let column = column - offset_x;
-                                        let x = column / width_per_point;
-                                        let rem = column.rem_euclid(width_per_point);
-                                        if rem < boundary_thickness
-                                            || rem + boundary_thickness >= width_per_point
-                                        {
-                                            is_boundary = true;
-                                        }
-                                        shown_rectangle.left_top.x + x as i32
-                                    };
-                                    let y = row * delta.y / height_per_data;
-                                    let y = shown_rectangle.left_top.y + y as i32;
-                                    RenderPoint {
-                                        coordinate: CoordinatePoint { x, y },
-                                        is_boundary,
-                                    }
-                                };
-                                self.update_color(
+                            let color = match self.downsampling {
+                                Downsampling::Average
+                                    if delta.x > width_per_data || delta.y > height_per_data =>
+                                {
+                                    let (x_start, x_end) = pixel_to_points(
+                                        column,
+                                        width_per_data,
+                                        delta.x,
+                                        shown_rectangle.left_top.x,
+                                    );
+                                    let (y_start, y_end) = pixel_to_points(
+                                        row,
+                                        height_per_data,
+                                        delta.y,
+                                        shown_rectangle.left_top.y,
+                                    );
+                                    let colors = (y_start..y_end)
+                                        .flat_map(|y| (x_start..x_end).map(move |x| (x, y)))
+                                        .filter_map(|(x, y)| {
+                                            data.lookup(
+                                                &(&shown_rectangle.left_top
+                                                    + CoordinateVec { x, y }),
+                                            )
+                                        });
+                                    Color::average(colors)
+                                }
+                                _ if bilinear => bilinear_color(
                                     data,
-                                    render_point,
-                                    row,
-                                    data_row,
-                                    height_per_data,
-                                    column,
-                                    data_column,
-                                    width_per_data,
-                                    &mut rendered,
-                                    width,
-                                    state,
-                                );
-                            }
-                        }
-                        None
-                    } else if width_per_point == 0 && height_per_point > 0 {
-                        let boundary_thickness = if height_per_point
-                            > self.boundary_factor_min * self.boundary_unselected.thickness
-                        {
-                            self.boundary_unselected.thickness
-                        } else {
-                            0
-                        };
-                        let offset_y = (height_per_data.rem_euclid(height_per_point) + 1) / 2;
-                        for row in 0..height_per_data {
-                            for column in 0..width_per_data {
-                                let render_point = {
-                                    let mut is_boundary = false;
-                                    let x = column * delta.x / width_per_data;
-                                    let x = shown_rectangle.left_top.x + x as i32;
-                                    let y = if row < offset_y {
-                                        if row + boundary_thickness >= offset_y {
-                                            is_boundary = true;
-                                        }
-                                        shown_rectangle.left_top.y - 1
-                                    } else {
-                                        let row = row - offset_y;
-                                        let y = row / height_per_point;
-                                        let rem = row.rem_euclid(height_per_point);
-                                        if rem < boundary_thickness
-                                            || rem + boundary_thickness >= height_per_point
-                                        {
-                                            is_boundary = true;
+                                    &render_point.coordinate,
+                                    &shown_rectangle.left_top,
+                                    [
+                                        (column, width_per_data, delta.x),
+                                        (row, height_per_data, delta.y),
+                                    ],
+                                ),
+                                _ => data.lookup(&render_point.coordinate),
+                            };
+                            // grid lines are drawn on top of the data, but below the boundaries
+                            let color = match &self.grid {
+                                Some(grid) => color.map(|c| {
+                                    let x = grid.hit(
+                                        column,
+                                        width_per_data,
+                                        delta.x,
+                                        shown_rectangle.left_top.x,
+                                    );
+                                    let y = grid.hit(
+                                        row,
+                                        height_per_data,
+                                        delta.y,
+                                        shown_rectangle.left_top.y,
+                                    );
+                                    match (x, y) {
+                                        (Some(true), _) | (_, Some(true)) => grid.color.clone(),
+                                        (Some(false), _) | (_, Some(false)) => {
+                                            grid.minor_color.clone()
                                         }
-                                        shown_rectangle.left_top.y + y as i32
-                                    };
-                                    RenderPoint {
-                                        coordinate: CoordinatePoint { x, y },
-                                        is_boundary,
+                                        (None, None) => c,
                                     }
-                                };
-                                self.update_color(
-                                    data,
-                                    render_point,
-                                    row,
-                                    data_row,
-                                    height_per_data,
-                                    column,
-                                    data_column,
-                                    width_per_data,
-                                    &mut rendered,
-                                    width,
-                                    state,
-                                );
-                            }
-                        }
-                        None
-                    } else {
-                        for row in 0..height_per_data {
-                            for column in 0..width_per_data {
-                                let render_point = {
-                                    let x = column * delta.x / width_per_data;
-                                    let y = row * delta.y / height_per_data;
-                                    let offset = CoordinateVec { x, y };
-                                    let point = &shown_rectangle.left_top + offset;
-                                    RenderPoint {
-                                        coordinate: point,
-                                        is_boundary: false,
-                                    }
-                                };
-                                self.update_color(
-                                    data,
-                                    render_point,
-                                    row,
-                                    data_row,
-                                    height_per_data,
-                                    column,
-                                    data_column,
-                                    width_per_data,
-                                    &mut rendered,
-                                    width,
-                                    state,
-                                );
-                            }
+                                }),
+                                None => color,
+                            };
+                            self.update_color(
+                                color,
+                                render_point,
+                                row,
+                                data_row,
+                                height_per_data,
+                                column,
+                                data_column,
+                                width_per_data,
+                                &mut rendered,
+                                render_width,
+                                state,
+                            );
                         }
-                        None
-                    }; // add title
+                    }
+                    self.draw_drag_outline(
+                        &shown_rectangle,
+                        [width_per_data, height_per_data],
+                        [data_column, data_row],
+                        &mut rendered,
+                        render_width,
+                    );
+                    // add title
                     {
                         let title = &data.overlay.title;
-                        let mut font = data.overlay.font.clone();
+                        let mut font = data.overlay.title_font.clone();
                         let mut title_to_draw = None;
-                        while font.font_height > 8. {
-                            if let Some(title) = font.render(title) {
+                        while font.font_height > self.min_font_height.max(1.) {
+                            if let Some(title) = font.render_opt(title) {
                                 if (title.width as usize) < (width_per_data * 8 / 10) {
                                     title_to_draw = Some(title);
                                     break;
@@ -727,82 +1696,110 @@ impl<Key: std::hash::Hash + Eq + Clone, Color: Clone + GammyMultiplyable + BitMa
                             }
                             font.font_height -= 1.0;
                         }
+                        if title_to_draw.is_none() && self.truncate_titles {
+                            title_to_draw =
+                                truncate_with_ellipsis(title, &font, width_per_data * 8 / 10);
+                        }
                         if let Some(title) = title_to_draw {
                             draw_axis_label(
                                 &mut rendered,
                                 &title,
                                 data_column
                                     * (width_per_data + self.boundary_between_data.thickness)
-                                    + (width_per_data.saturating_sub(title.width as usize)) / 2,
+                                    + if font.rtl {
+                                        width_per_data.saturating_sub(title.width as usize)
+                                    } else {
+                                        (width_per_data.saturating_sub(title.width as usize)) / 2
+                                    },
                                 data_row * (height_per_data + self.boundary_between_data.thickness),
                                 render_width,
-                                data.overlay.font.background_is_transparent,
+                                &data.overlay.title_font,
                                 &self.background,
+                                &self.text_compositing,
                             );
                         }
                     }
                     // add overlays
-                    if let Some((ox, oy)) = overlay_offset_lt {
+                    if width_per_point > 0 && height_per_point > 0 {
                         for (pos, bitmap) in data.overlay.get_overlays() {
                             if pos.x >= shown_rectangle.left_top.x
                                 && pos.y >= shown_rectangle.left_top.y
                                 && pos.x < shown_rectangle.right_bottom.x
                                 && pos.y < shown_rectangle.right_bottom.y
-                                && bitmap.width as usize <= width_per_point
-                                && bitmap.height as usize <= height_per_point
                             {
                                 let dx = (pos.x - shown_rectangle.left_top.x) as usize;
                                 let dy = (pos.y - shown_rectangle.left_top.y) as usize;
-                                draw_axis_label(
-                                    &mut rendered,
-                                    bitmap,
-                                    data_column
-                                        * (width_per_data + self.boundary_between_data.thickness)
-                                        + ox
-                                        + dx * width_per_point
-                                        + width_per_point.saturating_sub(bitmap.width as usize) / 2,
-                                    data_row
-                                        * (height_per_data + self.boundary_between_data.thickness)
-                                        + oy
-                                        + dy * height_per_point
-                                        + height_per_point.saturating_sub(bitmap.height as usize)
-                                            / 2,
-                                    render_width,
-                                    data.overlay.font.background_is_transparent,
-                                    &self.background,
+                                let (left, right) = cell_range(
+                                    dx,
+                                    width_per_data,
+                                    delta.x,
+                                    shown_rectangle.left_top.x,
                                 );
+                                let (top, bottom) = cell_range(
+                                    dy,
+                                    height_per_data,
+                                    delta.y,
+                                    shown_rectangle.left_top.y,
+                                );
+                                if bitmap.width as usize <= right - left
+                                    && bitmap.height as usize <= bottom - top
+                                {
+                                    draw_axis_label(
+                                        &mut rendered,
+                                        bitmap,
+                                        data_column
+                                            * (width_per_data
+                                                + self.boundary_between_data.thickness)
+                                            + left
+                                            + (right - left - bitmap.width as usize) / 2,
+                                        data_row
+                                            * (height_per_data
+                                                + self.boundary_between_data.thickness)
+                                            + top
+                                            + (bottom - top - bitmap.height as usize) / 2,
+                                        render_width,
+                                        &data.overlay.font,
+                                        &self.background,
+                                        &self.text_compositing,
+                                    );
+                                }
                             }
                         }
                     }
                     // add corners
-                    if data.overlay.show_coordinates {
+                    if !data.overlay.coordinate_corners.is_empty() {
                         let ShowRect {
                             left_top: ShowPoint { x: ltx, y: lty },
                             right_bottom: ShowPoint { x: rbx, y: rby },
                         } = state.shown_rectangle.clone().unwrap_or_default();
                         let rbx = rbx - 1;
                         let rby = rby - 1;
-                        let lt = data.overlay.font.render(&format!("{ltx}|{lty}"));
-                        let lb = data.overlay.font.render(&format!("{ltx}|{rby}"));
-                        let rt = data.overlay.font.render(&format!("{rbx}|{lty}"));
-                        let rb = data.overlay.font.render(&format!("{rbx}|{rby}"));
-                        let lt = lt.map(|x| ((0, 0), x));
-                        let lb: Option<((usize, usize), BitMapText)> = lb.map(|x: BitMapText| {
-                            ((0, height_per_data.saturating_sub(x.height as usize)), x)
-                        });
-                        let rt = rt.map(|x: BitMapText| {
-                            ((width_per_data.saturating_sub(x.width as usize), 0), x)
-                        });
-                        let rb = rb.map(|x: BitMapText| {
-                            (
-                                (
-                                    width_per_data.saturating_sub(x.width as usize),
-                                    height_per_data.saturating_sub(x.height as usize),
-                                ),
-                                x,
-                            )
-                        });
-                        for ((dx, dy), font) in [lt, lb, rt, rb].into_iter().flatten() {
+                        for corner in &data.overlay.coordinate_corners {
+                            let (x, y) = match corner {
+                                Corner::LeftTop => (ltx, lty),
+                                Corner::RightTop => (rbx, lty),
+                                Corner::LeftBottom => (ltx, rby),
+                                Corner::RightBottom => (rbx, rby),
+                            };
+                            let font = if let Some(font) =
+                                data.overlay.coordinate_font.render_opt(&format!("{x}|{y}"))
+                            {
+                                font
+                            } else {
+                                continue;
+                            };
+                            let dx = match corner {
+                                Corner::LeftTop | Corner::LeftBottom => 0,
+                                Corner::RightTop | Corner::RightBottom => {
+                                    width_per_data.saturating_sub(font.width as usize)
+                                }
+                            };
+                            let dy = match corner {
+                                Corner::LeftTop | Corner::RightTop => 0,
+                                Corner::LeftBottom | Corner::RightBottom => {
+                                    height_per_data.saturating_sub(font.height as usize)
+                                }
+                            };
                             draw_axis_label(
                                 &mut rendered,
                                 &font,
@@ -812,94 +1809,390 @@ impl<Key: std::hash::Hash + Eq + Clone, Color: Clone + GammyMultiplyable + BitMa
                                 data_row * (height_per_data + self.boundary_between_data.thickness)
                                     + dy,
                                 render_width,
-                                data.overlay.font.background_is_transparent,
+                                &data.overlay.coordinate_font,
                                 &self.background,
+                                &self.text_compositing,
                             );
                         }
                     }
+                    // add scale bar
+                    if let Some(scale_bar) = &self.scale_bar {
+                        self.draw_scale_bar(
+                            scale_bar,
+                            &mut rendered,
+                            render_width,
+                            (
+                                data_column
+                                    * (width_per_data + self.boundary_between_data.thickness),
+                                data_row * (height_per_data + self.boundary_between_data.thickness),
+                            ),
+                            [width_per_data, height_per_data],
+                            delta.x,
+                            &data.overlay.coordinate_font,
+                        );
+                    }
                 }
             }
         }
 
-        // add colorbar
-        if let Some((gradient, thickness, (lower, upper))) = &self.colorbar {
-            let thickness = *thickness;
-            for row in 0..height {
-                for column in 0..self.boundary_between_data.thickness {
-                    let column = width - self.boundary_between_data.thickness - thickness + column;
-                    rendered[column + row * width] = self.boundary_between_data.color.clone();
-                }
+        if let Some(legend) = &self.legend {
+            let plot_height = data_rows * height_per_data
+                + self.boundary_between_data.thickness * (data_rows - 1);
+            self.draw_legend(
+                legend,
+                &mut rendered,
+                render_width,
+                [plot_width, plot_height],
+            );
+        }
+
+        // place the plot area into the image and add the colorbar
+        let (separator, colorbar) = match colorbar_area {
+            Some(areas) => areas,
+            None => return Ok(rendered),
+        };
+        let [plot_left, plot_top, _, _] = plot_area;
+        let mut image = vec![self.background.clone(); width * height];
+        if area_width > 0 {
+            for (row, pixels) in rendered.chunks(area_width).enumerate() {
+                let start = plot_left + (plot_top + row) * width;
+                image[start..start + area_width].clone_from_slice(pixels);
             }
-            for row in 0..height {
-                for column in 0..thickness {
-                    let column = width - thickness + column;
-                    let c = gradient.element_at(height - 1 - row, height).remove_alpha();
-                    rendered[column + row * width] = c;
-                }
+        }
+        let [left, top, separator_width, separator_height] = separator;
+        for row in top..top + separator_height {
+            for column in left..left + separator_width {
+                image[column + row * width] = self.boundary_between_data.color.clone();
             }
-            if let Some(font) = self.data.first().map(|d| &d.data.overlay.font) {
-                fn string_representation(value: f32, precision: usize) -> String {
-                    let mut num = format!("{value:+3.precision$E}");
-                    let exp = num.split_off(num.find('E').unwrap());
-                    let (sign, exp) = if let Some(stripped) = exp.strip_prefix("E-") {
-                        ('-', stripped)
-                    } else {
-                        ('+', &exp[1..])
-                    };
-                    num.push_str(&format!("E{}{:0>pad$}", sign, exp, pad = 2));
-                    num
-                }
-                let count = 5; //TODO: make this configurable
-                let count = std::cmp::max(2, count);
-                for (i, f) in (0..count)
-                    .map(|i| lower + (upper - lower) / (count as f32 - 1.) * (i as f32))
-                    .rev()
-                    .enumerate()
-                {
-                    let mut bitmapfont = None;
-                    let mut font = font.clone();
-                    'outer: while font.font_height > 8. {
-                        for max_precision in (1..5).rev() {
-                            let s = string_representation(f, max_precision);
-                            if let Some(font) = BitMapText::new(&s, &font) {
-                                if font.width < thickness as i32 {
-                                    bitmapfont = Some(font);
-                                    break 'outer;
-                                }
-                            }
+        }
+        self.draw_colorbar(&mut image, width, colorbar);
+        Ok(image)
+    }
+
+    /// Draw the colorbar (including its labels) into the given pixel rectangle [left, top, width, height]
+    fn draw_colorbar(
+        &self,
+        rendered: &mut [Color],
+        render_width: usize,
+        [left, top, width, height]: [usize; 4],
+    ) {
+        let (gradient, thickness, limits) = match self.shown_colorbar(render_width) {
+            Some(colorbar) => colorbar,
+            None => return,
+        };
+        let (lower, upper) = self.scaled_colorbar_limits(limits);
+        let horizontal = self.colorbar_placement.is_horizontal();
+        for row in 0..height {
+            for column in 0..width {
+                let c = if horizontal {
+                    gradient.element_at(column, width)
+                } else {
+                    gradient.element_at(height - 1 - row, height)
+                };
+                rendered[left + column + (top + row) * render_width] = c.remove_alpha();
+            }
+        }
+        let font = self
+            .colorbar_font
+            .as_ref()
+            .or_else(|| self.data.first().map(|d| &d.data.overlay.font));
+        let font = match font {
+            Some(font) => font,
+            None => return,
+        };
+        let count = std::cmp::max(2, self.colorbar_ticks);
+        // the ticks are evenly spaced on the colorbar, but labeled in data units
+        let mut values = (0..count)
+            .map(|i| lower + (upper - lower) / (count as f64 - 1.) * (i as f64))
+            .map(|value| self.colorbar_scale.invert_f64(value))
+            .collect::<Vec<_>>();
+        // vertical colorbars show the highest value at the top
+        let length = if horizontal {
+            width
+        } else {
+            values.reverse();
+            height
+        };
+        for (i, f) in values.into_iter().enumerate() {
+            let mut bitmapfont = None;
+            let mut font = font.clone();
+            'outer: while font.font_height > self.min_font_height.max(1.) {
+                let candidates = match &self.colorbar_formatter {
+                    Some(format) => vec![format(f)],
+                    None => self.colorbar_labels.candidates(f),
+                };
+                for s in candidates {
+                    if let Ok(font) = BitMapText::new(&s, &font) {
+                        let fits = if horizontal {
+                            font.height < thickness as i32 && font.width as usize * count < length
+                        } else {
+                            font.width < thickness as i32
+                        };
+                        if fits {
+                            bitmapfont = Some(font);
+                            break 'outer;
                         }
-                        font.font_height -= 1.;
                     }
-                    let f = if let Some(bitmapfont) = bitmapfont {
-                        bitmapfont
-                    } else {
+                }
+                font.font_height -= 1.;
+            }
+            let f = if let Some(bitmapfont) = bitmapfont {
+                bitmapfont
+            } else {
+                continue;
+            };
+            let target_center = (length * i / (count - 1)) as i32;
+            // labels are aligned to the outer edge of the colorbar
+            let (label_left, label_top) = match self.colorbar_placement {
+                ColorbarPlacement::Right | ColorbarPlacement::Left => {
+                    if height as i32 <= f.height || width as i32 <= f.width {
                         continue;
+                    }
+                    let top = (target_center - f.height / 2).clamp(0, height as i32 - f.height);
+                    let left = match self.colorbar_placement {
+                        ColorbarPlacement::Left => 0,
+                        _ => width as i32 - f.width,
                     };
-                    let target_center = (height * i / (count - 1)) as i32;
-                    let top = target_center - f.height / 2;
-                    if height as i32 > f.height && width as i32 > f.width {
-                        let top = top.clamp(0, height as i32 - f.height) as usize;
-                        let left = std::cmp::max(0, width as i32 - f.width) as usize;
-                        draw_axis_label(
-                            &mut rendered,
-                            &f,
-                            left,
-                            top,
-                            render_width,
-                            font.background_is_transparent,
-                            &self.background,
-                        );
+                    (left, top)
+                }
+                ColorbarPlacement::Top | ColorbarPlacement::Bottom => {
+                    if width as i32 <= f.width || height as i32 <= f.height {
+                        continue;
                     }
+                    let left = (target_center - f.width / 2).clamp(0, width as i32 - f.width);
+                    let top = match self.colorbar_placement {
+                        ColorbarPlacement::Top => 0,
+                        _ => height as i32 - f.height,
+                    };
+                    (left, top)
+                }
+            };
+            draw_axis_label(
+                rendered,
+                &f,
+                left + label_left as usize,
+                top + label_top as usize,
+                render_width,
+                &font,
+                &self.background,
+                &self.text_compositing,
+            );
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn draw_scale_bar(
+        &self,
+        scale_bar: &ScaleBar<Color>,
+        rendered: &mut [Color],
+        render_width: usize,
+        (x_offset, y_offset): (usize, usize),
+        [width_per_data, height_per_data]: [usize; 2],
+        points: usize,
+        font: &FontOptions,
+    ) {
+        const MARGIN: usize = 4;
+        let pixels_per_unit =
+            width_per_data as f64 / points as f64 / scale_bar.units_per_point.abs();
+        // the bar shall cover at most a quarter of the plot
+        let (length, decimals) = match nice_length(width_per_data as f64 / 4. / pixels_per_unit) {
+            Some(x) => x,
+            None => return,
+        };
+        let bar_width = (length * pixels_per_unit).round() as usize;
+        let label = font.render_opt(&format!("{length:.decimals$} {}", scale_bar.unit));
+        let label_height = label.as_ref().map(|l| l.height as usize).unwrap_or(0);
+        let total_height = scale_bar.thickness + label_height;
+        if bar_width == 0 || bar_width + 2 * MARGIN > width_per_data {
+            return;
+        }
+        if total_height + 2 * MARGIN > height_per_data {
+            return;
+        }
+        let left = match scale_bar.position {
+            Corner::LeftTop | Corner::LeftBottom => MARGIN,
+            Corner::RightTop | Corner::RightBottom => width_per_data - MARGIN - bar_width,
+        };
+        let top = match scale_bar.position {
+            Corner::LeftTop | Corner::RightTop => MARGIN,
+            Corner::LeftBottom | Corner::RightBottom => height_per_data - MARGIN - total_height,
+        };
+        for row in 0..scale_bar.thickness {
+            for column in 0..bar_width {
+                let x = x_offset + left + column;
+                let y = y_offset + top + row;
+                rendered[x + y * render_width] = scale_bar.color.clone();
+            }
+        }
+        if let Some(label) = label {
+            if (label.width as usize) <= width_per_data {
+                let label_left = (left + bar_width / 2)
+                    .saturating_sub(label.width as usize / 2)
+                    .min(width_per_data - label.width as usize);
+                draw_axis_label(
+                    rendered,
+                    &label,
+                    x_offset + label_left,
+                    y_offset + top + scale_bar.thickness,
+                    render_width,
+                    font,
+                    &self.background,
+                    &self.text_compositing,
+                );
+            }
+        }
+    }
+
+    /// Draw swatches of the selection boundary colors and the background, each followed by its label
+    fn draw_legend(
+        &self,
+        legend: &Legend,
+        rendered: &mut [Color],
+        render_width: usize,
+        [plot_width, plot_height]: [usize; 2],
+    ) {
+        const MARGIN: usize = 4;
+        let entries = [
+            (&legend.text_selected, &self.boundary_selected),
+            (&legend.text_unselected, &self.boundary_unselected.color),
+            (&legend.text_no_data, &self.background),
+        ]
+        .into_iter()
+        .filter_map(|(text, color)| Some((legend.font.render_opt(text)?, color)))
+        .collect::<Vec<_>>();
+        let swatch = match entries.iter().map(|(label, _)| label.height as usize).max() {
+            Some(swatch) => swatch,
+            None => return,
+        };
+        let label_width = entries
+            .iter()
+            .map(|(label, _)| label.width as usize)
+            .max()
+            .unwrap_or(0);
+        let box_width = 3 * MARGIN + swatch + label_width;
+        let box_height = MARGIN + entries.len() * (swatch + MARGIN);
+        if box_width + 2 * MARGIN > plot_width || box_height + 2 * MARGIN > plot_height {
+            return;
+        }
+        let left = match legend.position {
+            Corner::LeftTop | Corner::LeftBottom => MARGIN,
+            Corner::RightTop | Corner::RightBottom => plot_width - MARGIN - box_width,
+        };
+        let top = match legend.position {
+            Corner::LeftTop | Corner::RightTop => MARGIN,
+            Corner::LeftBottom | Corner::RightBottom => plot_height - MARGIN - box_height,
+        };
+        let mut fill = |left: usize, top: usize, width: usize, height: usize, color: &Color| {
+            for y in top..top + height {
+                for x in left..left + width {
+                    rendered[x + y * render_width] = color.clone();
+                }
+            }
+        };
+        let box_color = &self.boundary_between_data.color;
+        fill(left, top, box_width, box_height, box_color);
+        for (i, (_, color)) in entries.iter().enumerate() {
+            let swatch_top = top + MARGIN + i * (swatch + MARGIN);
+            // framed, so that the background is visible as a swatch
+            fill(left + MARGIN, swatch_top, swatch, swatch, &Color::gray(255));
+            if swatch > 2 {
+                fill(
+                    left + MARGIN + 1,
+                    swatch_top + 1,
+                    swatch - 2,
+                    swatch - 2,
+                    color,
+                );
+            }
+        }
+        for (i, (label, _)) in entries.iter().enumerate() {
+            draw_axis_label(
+                rendered,
+                label,
+                left + 2 * MARGIN + swatch,
+                top + MARGIN + i * (swatch + MARGIN) + (swatch - label.height as usize) / 2,
+                render_width,
+                &legend.font,
+                box_color,
+                &self.text_compositing,
+            );
+        }
+    }
+
+    /// Boundary thickness in x- and y-direction. The boundary is only drawn along directions where a data point covers at least one pixel
+    fn boundary_thickness(
+        &self,
+        width_per_point: usize,
+        height_per_point: usize,
+    ) -> (usize, usize) {
+        let thickness = self.boundary_unselected.thickness;
+        let min = self.boundary_factor_min * thickness;
+        let large_enough = (width_per_point == 0 || width_per_point > min)
+            && (height_per_point == 0 || height_per_point > min);
+        if large_enough {
+            (
+                if width_per_point > 0 { thickness } else { 0 },
+                if height_per_point > 0 { thickness } else { 0 },
+            )
+        } else {
+            (0, 0)
+        }
+    }
+
+    /// Draw the outline of the drag rectangle into the subplot at the given column/row
+    fn draw_drag_outline(
+        &self,
+        shown_rectangle: &CoordinateRect,
+        [width_per_data, height_per_data]: [usize; 2],
+        [data_column, data_row]: [usize; 2],
+        rendered: &mut [Color],
+        width: usize,
+    ) {
+        let (((lt, rb), _), outline) = match (&self.drag_area, &self.drag_outline) {
+            (Some(drag_area), Some(outline)) if outline.thickness > 0 => (drag_area, outline),
+            _ => return,
+        };
+        let delta = shown_rectangle.delta();
+        // first pixel of the given data point offset, which may be outside of the subplot
+        let edge = |offset: i32, pixels: usize, points: usize| {
+            let (offset, pixels, points) = (offset as i64, pixels as i64, points as i64);
+            -((-offset * pixels).div_euclid(points))
+        };
+        let x0 = edge(lt.x - shown_rectangle.left_top.x, width_per_data, delta.x);
+        let x1 = edge(
+            rb.x + 1 - shown_rectangle.left_top.x,
+            width_per_data,
+            delta.x,
+        );
+        let y0 = edge(lt.y - shown_rectangle.left_top.y, height_per_data, delta.y);
+        let y1 = edge(
+            rb.y + 1 - shown_rectangle.left_top.y,
+            height_per_data,
+            delta.y,
+        );
+        let thickness = outline.thickness as i64;
+        let color = outline.color.clone().remove_alpha();
+        for row in y0.max(0)..y1.min(height_per_data as i64) {
+            for column in x0.max(0)..x1.min(width_per_data as i64) {
+                let is_outline = column < x0 + thickness
+                    || column >= x1 - thickness
+                    || row < y0 + thickness
+                    || row >= y1 - thickness;
+                if is_outline {
+                    let row = row as usize
+                        + data_row * (height_per_data + self.boundary_between_data.thickness);
+                    let column = column as usize
+                        + data_column * (width_per_data + self.boundary_between_data.thickness);
+                    rendered[column + row * width] = color.clone();
                 }
             }
         }
-        Ok(rendered)
     }
 
     #[allow(clippy::too_many_arguments)]
     fn update_color(
         &self,
-        data: &Data<Color>,
+        color: Option<Color>,
         RenderPoint {
             coordinate,
             is_boundary,
@@ -914,15 +2207,22 @@ impl<Key: std::hash::Hash + Eq + Clone, Color: Clone + GammyMultiplyable + BitMa
         width: usize,
         state: &MultimapState<Key>,
     ) {
-        let c = if let Some(c) = data.lookup(&coordinate) {
-            if is_boundary {
-                if state.selected.contains(&coordinate) {
+        let selected = state.selected.contains(&coordinate);
+        let c = if let Some(c) = color {
+            let c = if is_boundary {
+                if selected && self.selection_style == SelectionStyle::Outline {
                     self.boundary_selected.clone()
                 } else {
                     self.boundary_unselected.color.clone()
                 }
             } else {
                 c
+            };
+            match self.selection_style {
+                SelectionStyle::Fill(opacity) if selected => {
+                    c.blend(self.boundary_selected.clone(), opacity)
+                }
+                _ => c,
             }
         } else {
             self.background.clone()
@@ -933,7 +2233,7 @@ impl<Key: std::hash::Hash + Eq + Clone, Color: Clone + GammyMultiplyable + BitMa
                 && coordinate.x <= rb.x
                 && coordinate.y <= rb.y
             {
-                c.gamma_multiply(0.5)
+                c.gamma_multiply(self.drag_dim_factor)
             } else {
                 c
             }
@@ -970,28 +2270,35 @@ impl<Key: std::hash::Hash + Eq + Clone, Color: Clone + GammyMultiplyable + BitMa
         if count == 0 {
             return crate::MultiMapPosition::NotHovering;
         }
-        let (data_columns, data_rows) = compute_columns_rows(count);
-        assert!(data_columns > 0);
-        assert!(data_rows > 0);
-        let (width_per_data, height_per_data) = {
-            let cb_thickness = self
-                .colorbar
-                .as_ref()
-                .map(|(_, thickness, _)| *thickness + self.boundary_between_data.thickness)
-                .unwrap_or(0);
-            let width_without_colorbar = if width >= cb_thickness {
-                width - cb_thickness
-            } else {
-                return crate::MultiMapPosition::NotHovering;
+        let ([plot_left, plot_top, plot_area_width, plot_area_height], colorbar) =
+            match self.split_colorbar([width, height]) {
+                Ok(split) => split,
+                Err(_) => return crate::MultiMapPosition::NotHovering,
             };
-            let width_without_colorbar_and_boundaries =
-                width_without_colorbar - self.boundary_between_data.thickness * (data_columns - 1);
-            let width_per_data = width_without_colorbar_and_boundaries / data_columns;
-            let height_without_colorbar_and_boundaries =
-                height - self.boundary_between_data.thickness * (data_rows - 1);
-            let height_per_data = height_without_colorbar_and_boundaries / data_rows;
-            (width_per_data, height_per_data)
-        };
+        if let (Some((_, [left, top, bar_width, bar_height])), Some((g, _, limits))) =
+            (colorbar, self.shown_colorbar(width))
+        {
+            let (lower, upper) = self.scaled_colorbar_limits(limits);
+            // the boundary between plot and colorbar is not part of the colorbar
+            if (left..left + bar_width).contains(&column) && (top..top + bar_height).contains(&row)
+            {
+                // same step mapping as used for rendering, i.e. top/right = upper, bottom/left = lower
+                let f = if self.colorbar_placement.is_horizontal() {
+                    g.fetch_value(lower, upper, column - left, bar_width)
+                } else {
+                    g.fetch_value(lower, upper, top + bar_height - 1 - row, bar_height)
+                };
+                return crate::MultiMapPosition::Colorbar(self.colorbar_scale.invert_f64(f));
+            }
+        }
+        if !(plot_left..plot_left + plot_area_width).contains(&column)
+            || !(plot_top..plot_top + plot_area_height).contains(&row)
+        {
+            return crate::MultiMapPosition::NotHovering;
+        }
+        let (column, row) = (column - plot_left, row - plot_top);
+        let (data_columns, _, width_per_data, height_per_data) =
+            self.layout_plot_area(count, plot_area_width, plot_area_height);
         let data_column = column / width_per_data;
         let data_row = row / height_per_data;
         let data_index = data_row * data_columns + data_column;
@@ -999,141 +2306,23 @@ impl<Key: std::hash::Hash + Eq + Clone, Color: Clone + GammyMultiplyable + BitMa
             + self.boundary_between_data.thickness * (data_columns - 1);
         if column < plot_width {
             if let Some((key, data)) = data_sets.get(data_index) {
-                let shown_rectangle = &state.shown_rectangle.clone().unwrap_or_default()
-                    - &CoordinatePoint { x: 0, y: 0 };
+                let shown_rectangle =
+                    CoordinateRect::from(&state.shown_rectangle.clone().unwrap_or_default());
                 let delta = shown_rectangle.delta();
                 let width_per_point = width_per_data / delta.x;
                 let height_per_point = height_per_data / delta.y;
                 let row = row % height_per_data;
                 let column = column % width_per_data;
-                let render_point = if width_per_point > 0 && height_per_point > 0 {
-                    let boundary_thickness = {
-                        if width_per_point
-                            > self.boundary_factor_min * self.boundary_unselected.thickness
-                            && height_per_point
-                                > self.boundary_factor_min * self.boundary_unselected.thickness
-                        {
-                            self.boundary_unselected.thickness
-                        } else {
-                            0
-                        }
-                    };
-                    let offset_x = (width_per_data.rem_euclid(width_per_point) + 1) / 2;
-                    let offset_y = (height_per_data.rem_euclid(height_per_point) + 1) / 2;
-                    let mut is_boundary = false;
-                    let x = if column < offset_x {
-                        if column + boundary_thickness >= offset_x {
-                            is_boundary = true;
-                        }
-                        shown_rectangle.left_top.x - 1
-                    } else {
-                        let column = column - offset_x;
-                        let x = column / width_per_point;
-                        let rem = column.rem_euclid(width_per_point);
-                        if rem < boundary_thickness || rem + boundary_thickness >= width_per_point {
-                            is_boundary = true;
-                        }
-                        shown_rectangle.left_top.x + x as i32
-                    };
-                    let y = if row < offset_y {
-                        if row + boundary_thickness >= offset_y {
-                            is_boundary = true;
-                        }
-                        shown_rectangle.left_top.y - 1
-                    } else {
-                        let row = row - offset_y;
-                        let y = row / height_per_point;
-                        let rem = row.rem_euclid(height_per_point);
-                        if rem < boundary_thickness || rem + boundary_thickness >= height_per_point
-                        {
-                            is_boundary = true;
-                        }
-                        shown_rectangle.left_top.y + y as i32
-                    };
-                    RenderPoint {
-                        coordinate: CoordinatePoint { x, y },
-                        is_boundary,
-                    }
-                } else if width_per_point > 0 && height_per_point == 0 {
-                    let boundary_thickness = {
-                        if width_per_point
-                            > self.boundary_factor_min * self.boundary_unselected.thickness
-                        {
-                            self.boundary_unselected.thickness
-                        } else {
-                            0
-                        }
-                    };
-                    let offset_x = (width_per_data.rem_euclid(width_per_point) + 1) / 2;
-                    let mut is_boundary = false;
-                    let x = if column < offset_x {
-                        if column + boundary_thickness >= offset_x {
-                            is_boundary = true;
-                        }
-                        shown_rectangle.left_top.x - 1
-                    } else {
-                        let column = column - offset_x;
-                        let x = column / width_per_point;
-                        let rem = column.rem_euclid(width_per_point);
-                        if rem < boundary_thickness || rem + boundary_thickness >= width_per_point {
-                            is_boundary = true;
-                        }
-                        shown_rectangle.left_top.x + x as i32
-                    };
-                    let y = row * delta.y / height_per_data;
-                    let y = shown_rectangle.left_top.y + y as i32;
-                    RenderPoint {
-                        coordinate: CoordinatePoint { x, y },
-                        is_boundary,
-                    }
-                } else if width_per_point == 0 && height_per_point > 0 {
-                    let boundary_thickness = {
-                        if height_per_point
-                            > self.boundary_factor_min * self.boundary_unselected.thickness
-                        {
-                            self.boundary_unselected.thickness
-                        } else {
-                            0
-                        }
-                    };
-                    let offset_y = (height_per_data.rem_euclid(height_per_point) + 1) / 2;
-
-                    let mut is_boundary = false;
-                    let x = column * delta.x / width_per_data;
-                    let x = shown_rectangle.left_top.x + x as i32;
-                    let y = if row < offset_y {
-                        if row + boundary_thickness >= offset_y {
-                            is_boundary = true;
-                        }
-                        shown_rectangle.left_top.y - 1
-                    } else {
-                        let row = row - offset_y;
-                        let y = row / height_per_point;
-                        let rem = row.rem_euclid(height_per_point);
-                        if rem < boundary_thickness || rem + boundary_thickness >= height_per_point
-                        {
-                            is_boundary = true;
-                        }
-                        shown_rectangle.left_top.y + y as i32
-                    };
-                    RenderPoint {
-                        coordinate: CoordinatePoint { x, y },
-                        is_boundary,
-                    }
-                } else {
-                    let x = column * delta.x / width_per_data;
-                    let y = row * delta.y / height_per_data;
-                    let offset = CoordinateVec { x, y };
-                    let point = &shown_rectangle.left_top + offset;
-                    RenderPoint {
-                        coordinate: point,
-                        is_boundary: false,
-                    }
-                };
                 let RenderPoint {
                     coordinate,
                     is_boundary: _,
-                } = render_point;
+                } = render_point(
+                    column,
+                    row,
+                    [width_per_data, height_per_data],
+                    &shown_rectangle,
+                    self.boundary_thickness(width_per_point, height_per_point),
+                );
                 let key: &Key = key;
                 let key: Key = key.clone();
                 if data.lookup(&coordinate).is_some() {
@@ -1144,33 +2333,45 @@ impl<Key: std::hash::Hash + Eq + Clone, Color: Clone + GammyMultiplyable + BitMa
             } else {
                 crate::MultiMapPosition::NotHovering
             }
-        } else if let Some((g, thickness, (lower, upper))) = &self.colorbar {
-            if column + thickness >= width {
-                let relative_distance = (row as f32) / (height as f32); // this is a number between 0 and 1
-                let f = g.fetch_value(*lower, *upper, 1. - relative_distance);
-                crate::MultiMapPosition::Colorbar(f)
-            } else {
-                crate::MultiMapPosition::NotHovering
-            }
         } else {
             crate::MultiMapPosition::NotHovering
         }
     }
 
-    pub(crate) fn zoom(&mut self, zoom_increment: i32, shown_rectangle: &mut ShowRect) {
-        if zoom_increment < 0
-            || (shown_rectangle.right_bottom.x - shown_rectangle.left_top.x
-                > 3 + zoom_increment * 2)
+    /// Zoom in (positive increments) or out (negative increments), independently per axis
+    pub(crate) fn zoom(
+        &mut self,
+        [x_increment, y_increment]: [i32; 2],
+        shown_rectangle: &mut ShowRect,
+    ) {
+        let (dx, dy) = shown_rectangle.extent();
+        let x_increment = self.limit_zoom_increment(x_increment, dx);
+        let y_increment = self.limit_zoom_increment(y_increment, dy);
+        if x_increment < 0
+            || (shown_rectangle.right_bottom.x - shown_rectangle.left_top.x > 3 + x_increment * 2)
         {
-            shown_rectangle.left_top.x += zoom_increment;
-            shown_rectangle.right_bottom.x -= zoom_increment;
+            shown_rectangle.left_top.x += x_increment;
+            shown_rectangle.right_bottom.x -= x_increment;
         }
-        if zoom_increment < 0
-            || (shown_rectangle.right_bottom.y - shown_rectangle.left_top.y
-                > 3 + zoom_increment * 2)
+        if y_increment < 0
+            || (shown_rectangle.right_bottom.y - shown_rectangle.left_top.y > 3 + y_increment * 2)
         {
-            shown_rectangle.left_top.y += zoom_increment;
-            shown_rectangle.right_bottom.y -= zoom_increment;
+            shown_rectangle.left_top.y += y_increment;
+            shown_rectangle.right_bottom.y -= y_increment;
+        }
+        shown_rectangle.normalize();
+    }
+    /// Reduce a zoom increment (applied at both sides), such that the extent stays within 'min_shown_extent' and 'max_shown_extent'.
+    /// An extent which is already out of these bounds is not changed further in the wrong direction
+    fn limit_zoom_increment(&self, increment: i32, extent: i32) -> i32 {
+        match (self.min_shown_extent, self.max_shown_extent) {
+            (Some(min), _) if increment > 0 && extent - 2 * increment < min => {
+                increment.min((extent - min) / 2).max(0)
+            }
+            (_, Some(max)) if increment < 0 && extent - 2 * increment > max => {
+                increment.max(-(max - extent) / 2).min(0)
+            }
+            _ => increment,
         }
     }
 
@@ -1193,6 +2394,13 @@ impl<Key: std::hash::Hash + Eq + Clone, Color: Clone + GammyMultiplyable + BitMa
         shown_rectangle.left_top.y += delta.y;
         shown_rectangle.right_bottom.x += delta.x;
         shown_rectangle.right_bottom.y += delta.y;
+        shown_rectangle.normalize();
+        if let Some(pan_bounds) = &self.pan_bounds {
+            if !self.data.is_empty() {
+                let bounds = home_rect(&self.data, &Default::default());
+                shown_rectangle.keep_overlap(&bounds, pan_bounds.visible_fraction);
+            }
+        }
     }
 
     pub fn center_to(&mut self, pos: &CoordinatePoint, shown_rectangle: &mut ShowRect) {
@@ -1202,21 +2410,40 @@ impl<Key: std::hash::Hash + Eq + Clone, Color: Clone + GammyMultiplyable + BitMa
         shown_rectangle.left_top.y = pos.y - (dy - dy / 2);
         shown_rectangle.right_bottom.x = pos.x + dx / 2;
         shown_rectangle.right_bottom.y = pos.y + dy / 2;
+        shown_rectangle.normalize();
+    }
+
+    /// Move the shown rectangle as little as possible, such that it shows as much data as possible
+    pub fn snap_to_data(&self, state: &mut MultimapState<Key>) {
+        let bounds = home_rect(&self.data, &state.to_plot);
+        if let Some(shown_rectangle) = &mut state.shown_rectangle {
+            shown_rectangle.snap_into(&bounds);
+        }
     }
 
+    /// Returns false if the point was not added, since the maximal number of selected points is reached
+    /// Get the selection after clicking the given point.
+    /// Returns None if this would select more than 'max_selected' points
     pub fn select(
-        &mut self,
+        &self,
         pos: &CoordinatePoint,
         ctrl_is_pressed: bool,
-        selected: &mut std::collections::HashSet<CoordinatePoint>,
-    ) {
-        let was_selected_before = selected.remove(pos);
-        if !ctrl_is_pressed {
-            selected.clear();
-        }
+        selected: &std::collections::HashSet<CoordinatePoint>,
+        max_selected: Option<usize>,
+    ) -> Option<std::collections::HashSet<CoordinatePoint>> {
+        let mut new_selection = if ctrl_is_pressed {
+            selected.clone()
+        } else {
+            Default::default()
+        };
+        let was_selected_before = new_selection.remove(pos) || selected.contains(pos);
         if !was_selected_before {
-            selected.insert(pos.clone());
+            if max_selected.map_or(false, |max| new_selection.len() >= max) {
+                return None;
+            }
+            new_selection.insert(pos.clone());
         }
+        Some(new_selection)
     }
 
     pub fn drag_start(&mut self, pos: &CoordinatePoint) {
@@ -1265,11 +2492,229 @@ impl<Key: std::hash::Hash + Eq + Clone, Color: Clone + GammyMultiplyable + BitMa
         }
     }
 
+    /// Union of the bounding boxes of all shown data sets, i.e. the area shown after 'home'
+    pub(crate) fn data_bounds(&self, state: &MultimapState<Key>) -> Option<CoordinateRect> {
+        if !self.data.iter().any(|d| state.to_plot(&d.key)) {
+            return None;
+        }
+        Some(CoordinateRect::from(&home_rect(&self.data, &state.to_plot)))
+    }
     pub(crate) fn home(&self, state: &mut MultimapState<Key>) {
         state.shown_rectangle = Some(home_rect(&self.data, &state.to_plot));
     }
 }
 
+#[allow(clippy::too_many_arguments)]
+fn draw_axis_label<Color: BitMapDrawable + GammyMultiplyable + Clone>(
+    data: &mut [Color],
+    bitmapfont: &BitMapText,
+    x_offset: usize,
+    y_offset: usize,
+    render_width: usize,
+    font: &FontOptions,
+    background: &Color,
+    compositing: &TextCompositing<Color>,
+) {
+    for column in 0..bitmapfont.width {
+        for row in 0..bitmapfont.height {
+            let x = column as usize + x_offset;
+            let y = row as usize + y_offset;
+            let i = x + y * render_width;
+            let c = match (
+                font.background_is_transparent,
+                bitmapfont.fetch(column, row),
+            ) {
+                (true, None) => {
+                    /* nothing to do - but this should never occur*/
+                    continue;
+                }
+                (false, None) => background.clone(),
+
+                (true, Some(gray)) => {
+                    if let Some(c) = data.get(i) {
+                        // darken the data behind the text
+                        let c = if font.background_opacity > 0. {
+                            c.clone()
+                                .gamma_multiply(1. - font.background_opacity.min(1.))
+                                .remove_alpha()
+                        } else {
+                            c.clone()
+                        };
+                        composite_glyph(c, gray, compositing)
+                    } else {
+                        continue;
+                    }
+                }
+                (false, Some(gray)) => match compositing {
+                    TextCompositing::Additive => Color::gray(gray),
+                    TextCompositing::Over(_) => {
+                        composite_glyph(background.clone(), gray, compositing)
+                    }
+                },
+            };
+            data[i] = c;
+        }
+    }
+}
+
+/// Draw a glyph pixel of the given brightness onto a color
+fn composite_glyph<Color: BitMapDrawable + Clone>(
+    color: Color,
+    gray: u8,
+    compositing: &TextCompositing<Color>,
+) -> Color {
+    match compositing {
+        TextCompositing::Additive => color.saturating_add(gray),
+        TextCompositing::Over(text) => color.blend(text.clone(), gray as f32 / 255.),
+    }
+}
+
+/// Render the longest prefix of the text followed by an ellipsis, which is narrower than the given width
+fn truncate_with_ellipsis(text: &str, font: &FontOptions, max_width: usize) -> Option<BitMapText> {
+    // Font8x8 only covers ascii
+    let ellipsis = match font.font {
+        crate::Font::EguiMonospace => "\u{2026}",
+        crate::Font::Font8x8 => "...",
+    };
+    let chars = text.chars().collect::<Vec<_>>();
+    (0..chars.len()).rev().find_map(|n| {
+        let truncated = chars[..n].iter().collect::<String>() + ellipsis;
+        font.render_opt(&truncated)
+            .filter(|bitmap| (bitmap.width as usize) < max_width)
+    })
+}
+
+/// Largest "nice" length (1, 2 or 5 times a power of ten) not exceeding the given maximum.
+/// Also returns the number of decimals needed to print it
+fn nice_length(max: f64) -> Option<(f64, usize)> {
+    if !(max.is_finite() && max > 0.) {
+        return None;
+    }
+    let exponent = max.log10().floor() as i32;
+    let power = 10f64.powi(exponent);
+    let mantissa = [5., 2., 1.]
+        .into_iter()
+        .find(|m| m * power <= max)
+        .unwrap_or(1.);
+    let decimals = if exponent < 0 {
+        (-exponent) as usize
+    } else {
+        0
+    };
+    Some((mantissa * power, decimals))
+}
+
+/// Edge (left or top) of the data point with the given coordinate, in pixels, along one axis.
+/// The edges are anchored to the user-given coordinates instead of the shown rectangle.
+/// So each data point keeps its size in pixels while panning, even if the pixels per point are not an integer
+fn cell_edge(coordinate: i32, pixels: usize, points: usize) -> i64 {
+    div_ceil(coordinate as i64 * pixels as i64, points as i64)
+}
+/// Division rounding up, also for negative numerators
+fn div_ceil(numerator: i64, denominator: i64) -> i64 {
+    -(-numerator).div_euclid(denominator)
+}
+
+/// Pixel range [start, end) of the data point with the given offset, along one axis.
+/// 'first' is the coordinate of the first shown data point.
+/// The pixels are distributed evenly, so that the sizes of two data points differ by at most one pixel
+fn cell_range(point: usize, pixels: usize, points: usize, first: i32) -> (usize, usize) {
+    let origin = cell_edge(first, pixels, points);
+    let edge = |point: usize| (cell_edge(first + point as i32, pixels, points) - origin) as usize;
+    (edge(point), edge(point + 1))
+}
+
+/// Map a pixel to the offset of the shown data point, along one axis.
+/// 'first' is the coordinate of the first shown data point.
+/// Also returns whether the pixel is part of the boundary of this data point
+fn pixel_to_point(
+    pixel: usize,
+    pixels: usize,
+    points: usize,
+    first: i32,
+    boundary_thickness: usize,
+) -> (usize, bool) {
+    // the last data point whose edge is not behind this pixel
+    let position = pixel as i64 + cell_edge(first, pixels, points);
+    let point = ((position * points as i64).div_euclid(pixels as i64) - first as i64) as usize;
+    if boundary_thickness == 0 {
+        (point, false)
+    } else {
+        let (start, end) = cell_range(point, pixels, points, first);
+        let rem = pixel - start;
+        (
+            point,
+            rem < boundary_thickness || rem + boundary_thickness >= end - start,
+        )
+    }
+}
+
+/// Range [start, end) of the offsets of the data points covered by a pixel, along one axis.
+/// 'first' is the coordinate of the first shown data point
+fn pixel_to_points(pixel: usize, pixels: usize, points: usize, first: i32) -> (usize, usize) {
+    // the data points starting within this pixel, see 'cell_edge'
+    let position = pixel as i64 + cell_edge(first, pixels, points);
+    let offset = |position| {
+        let point = div_ceil(position * points as i64, pixels as i64) - first as i64;
+        point.clamp(0, points as i64) as usize
+    };
+    let (start, end) = (offset(position), offset(position + 1));
+    if start < end {
+        (start, end)
+    } else {
+        // more pixels than points
+        let (point, _) = pixel_to_point(pixel, pixels, points, first, 0);
+        (point, point + 1)
+    }
+}
+
+/// Color of a pixel, interpolated between the four data points around the pixel center.
+/// Each axis is given as (pixel, pixels, points). Returns None if the point under the pixel has no data.
+/// Neighbors without data are replaced by this point
+fn bilinear_color<Color: BitMapDrawable + Clone>(
+    data: &Data<Color>,
+    coordinate: &CoordinatePoint,
+    left_top: &CoordinatePoint,
+    axes: [(usize, usize, usize); 2],
+) -> Option<Color> {
+    let center = data.lookup(coordinate)?;
+    // position in points, relative to the center of the first shown point, see 'cell_edge'
+    let [(x, fx), (y, fy)] =
+        [(axes[0], left_top.x), (axes[1], left_top.y)].map(|((pixel, pixels, points), first)| {
+            let pixel = pixel as f64 + 0.5 + cell_edge(first, pixels, points) as f64;
+            let position = pixel * points as f64 / pixels as f64 - 0.5 - first as f64;
+            let start = position.floor();
+            (start as i32, (position - start) as f32)
+        });
+    let lookup = |dx, dy| {
+        data.lookup(&CoordinatePoint {
+            x: left_top.x + x + dx,
+            y: left_top.y + y + dy,
+        })
+        .unwrap_or_else(|| center.clone())
+    };
+    let top = lookup(0, 0).blend(lookup(1, 0), fx);
+    let bottom = lookup(0, 1).blend(lookup(1, 1), fx);
+    Some(top.blend(bottom, fy))
+}
+
+fn render_point(
+    column: usize,
+    row: usize,
+    [width_per_data, height_per_data]: [usize; 2],
+    shown_rectangle: &CoordinateRect,
+    (boundary_x, boundary_y): (usize, usize),
+) -> RenderPoint {
+    let delta = shown_rectangle.delta();
+    let first = &shown_rectangle.left_top;
+    let (x, is_boundary_x) = pixel_to_point(column, width_per_data, delta.x, first.x, boundary_x);
+    let (y, is_boundary_y) = pixel_to_point(row, height_per_data, delta.y, first.y, boundary_y);
+    RenderPoint {
+        coordinate: &shown_rectangle.left_top + CoordinateVec { x, y },
+        is_boundary: is_boundary_x || is_boundary_y,
+    }
+}
+
 pub(crate) fn home_rect<Key: std::hash::Hash + Eq, Color: Clone>(
     data: &[DataWithMetadata<Key, Color>],
     to_plot: &std::collections::HashMap<Key, bool>,
@@ -1305,6 +2750,100 @@ pub(crate) fn home_rect<Key: std::hash::Hash + Eq, Color: Clone>(
     }
 }
 
+#[cfg(test)]
+fn test_settings() -> ShowMultiMapSettings<char> {
+    ShowMultiMapSettings {
+        boundary_between_data: ColorWithThickness {
+            color: '-',
+            thickness: 2,
+        },
+        colorbar: Some((crate::colors::Gradient(vec!['a', 'b', 'c']), 4, (0., 1.))),
+        background: '.',
+        boundary_unselected: ColorWithThickness {
+            color: 'r',
+            thickness: 1,
+        },
+        boundary_selected: 'w',
+        boundary_factor_min: 3,
+        downsampling: Downsampling::Nearest,
+        scale_bar: None,
+        grid: None,
+        truncate_titles: false,
+        colorbar_labels: ColorbarLabels::Auto,
+        colorbar_relative_thickness: None,
+        colorbar_gap: None,
+        drag_dim_factor: 0.5,
+        drag_outline: None,
+        selection_style: SelectionStyle::Outline,
+        colorbar_font: None,
+        min_font_height: 8.,
+        text_compositing: TextCompositing::Additive,
+        legend: None,
+        colorbar_ticks: 5,
+        colorbar_placement: ColorbarPlacement::Right,
+        colorbar_formatter: None,
+        min_shown_extent: None,
+        max_shown_extent: None,
+        pan_bounds: None,
+        upsampling: Upsampling::Nearest,
+        colorbar_scale: crate::ScaleMode::Linear,
+    }
+}
+
+#[cfg(test)]
+fn colorbar_test_settings() -> ShowMultiMapSettings<char> {
+    ShowMultiMapSettings {
+        colorbar: Some((crate::colors::Gradient(vec!['a', 'b']), 100, (0., 1.))),
+        colorbar_font: Some(FontOptions {
+            font: crate::Font::EguiMonospace,
+            font_height: 12.,
+            ..Default::default()
+        }),
+        ..test_settings()
+    }
+}
+
+/// Render 4x4 data points into an image of 200x200 pixels, with the colorbar (if any) on the right half
+#[cfg(test)]
+fn render_with_colorbar(settings: ShowMultiMapSettings<char>) -> Vec<char> {
+    let data = Data {
+        width: 4,
+        height: 4,
+        data: vec!['x'; 16],
+        first_point_coordinate: CoordinatePoint { x: 0, y: 0 },
+        overlay: Overlay::example(&CoordinatePoint { x: 0, y: 0 }),
+    };
+    let map = ShowMultiMap::with_settings(vec![DataWithMetadata { key: 0, data }], settings);
+    let mut state = map.default_state();
+    map.render(200, 200, &mut state).unwrap()
+}
+
+/// Number of labels drawn onto the colorbar of 'render_with_colorbar', i.e. of separate groups of rows with text
+#[cfg(test)]
+fn colorbar_label_count(image: &[char]) -> usize {
+    let is_label = |c: &char| *c != 'a' && *c != 'b';
+    let rows = image
+        .chunks(200)
+        .map(|row| row[100..].iter().any(is_label))
+        .collect::<Vec<_>>();
+    rows.iter()
+        .zip(std::iter::once(&false).chain(rows.iter()))
+        .filter(|(row, before)| **row && !**before)
+        .count()
+}
+
+/// Number of colorbar columns of 'render_with_colorbar' with text, i.e. the width of the widest label
+#[cfg(test)]
+fn colorbar_label_columns(image: &[char]) -> usize {
+    (100..200)
+        .filter(|column| {
+            image
+                .chunks(200)
+                .any(|row| row[*column] != 'a' && row[*column] != 'b')
+        })
+        .count()
+}
+
 #[test]
 fn render_simple_tests() {
     fn dummy_data() -> ShowMultiMap<usize, char> {
@@ -1346,26 +2885,16 @@ fn render_simple_tests() {
                 overlay: Overlay::example(&CoordinatePoint { x: 1, y: 1 }),
             },
         ];
-        ShowMultiMap {
-            data: data
-                .into_iter()
+        ShowMultiMap::with_settings(
+            data.into_iter()
                 .enumerate()
                 .map(|(i, d)| DataWithMetadata { key: i, data: d })
                 .collect(),
-            boundary_between_data: ColorWithThickness {
-                color: '-',
-                thickness: 2,
-            },
-            colorbar: Some((crate::colors::Gradient(vec!['a', 'b', 'c']), 4, (0., 1.))),
-            background: '.',
-            boundary_unselected: ColorWithThickness {
-                color: 'r',
-                thickness: 1,
+            ShowMultiMapSettings {
+                boundary_factor_min: 7,
+                ..test_settings()
             },
-            boundary_selected: 'w',
-            boundary_factor_min: 7,
-            drag_area: None,
-        }
+        )
     }
     let width = 66;
     let height = 23;
@@ -1392,26 +2921,13 @@ fn render_simple_tests2() {
             first_point_coordinate: CoordinatePoint { x: -1, y: -1 },
             overlay: Overlay::example(&CoordinatePoint { x: 1, y: 1 }),
         }];
-        ShowMultiMap {
-            data: data
-                .into_iter()
+        ShowMultiMap::with_settings(
+            data.into_iter()
                 .enumerate()
                 .map(|(i, d)| DataWithMetadata { key: i, data: d })
                 .collect(),
-            boundary_between_data: ColorWithThickness {
-                color: '-',
-                thickness: 2,
-            },
-            colorbar: Some((crate::colors::Gradient(vec!['a', 'b', 'c']), 4, (0., 1.))),
-            background: '.',
-            boundary_unselected: ColorWithThickness {
-                color: 'r',
-                thickness: 1,
-            },
-            boundary_selected: 'w',
-            boundary_factor_min: 3,
-            drag_area: None,
-        }
+            test_settings(),
+        )
     }
     let width = 66;
     let height = 23;
@@ -1463,3 +2979,1281 @@ fn compute_columns_rows(count: usize) -> (usize, usize) {
     }
     (data_columns, data_rows)
 }
+
+#[test]
+fn show_rect_interpolate_test() {
+    let from = ShowRect {
+        left_top: ShowPoint { x: 0, y: 0 },
+        right_bottom: ShowPoint { x: 10, y: 10 },
+    };
+    let to = ShowRect {
+        left_top: ShowPoint { x: 4, y: -2 },
+        right_bottom: ShowPoint { x: 6, y: 20 },
+    };
+    assert!(from.interpolate(&to, 0.) == from);
+    assert!(from.interpolate(&to, 1.) == to);
+    assert!(from.interpolate(&to, 2.) == to);
+    let half = from.interpolate(&to, 0.5);
+    assert_eq!((half.left_top.x, half.left_top.y), (2, -1));
+    assert_eq!((half.right_bottom.x, half.right_bottom.y), (8, 15));
+}
+
+#[test]
+fn set_colorbar_limits_test() {
+    let mut map = ShowMultiMap::<usize, char>::with_settings(Vec::new(), test_settings());
+    assert!(!map.set_colorbar_limits(1., 1.));
+    assert!(!map.set_colorbar_limits(2., 1.));
+    assert!(!map.set_colorbar_limits(f64::NAN, 1.));
+    assert!(map.set_colorbar_limits(-1., 3.));
+    assert_eq!(map.colorbar.as_ref().map(|(_, _, l)| *l), Some((-1., 3.)));
+    map.set_colorbar(None);
+    assert!(!map.set_colorbar_limits(-1., 3.));
+}
+
+#[test]
+fn pan_keeps_extent_test() {
+    let mut map = ShowMultiMap::<usize, char>::with_settings(
+        Vec::new(),
+        ShowMultiMapSettings {
+            colorbar: None,
+            ..test_settings()
+        },
+    );
+    let mut rect = ShowRect {
+        left_top: ShowPoint { x: -3, y: 2 },
+        right_bottom: ShowPoint { x: 14, y: 9 },
+    };
+    let extent = rect.extent();
+    for direction in [
+        KeyBoardDirection::Left,
+        KeyBoardDirection::Up,
+        KeyBoardDirection::Right,
+        KeyBoardDirection::Down,
+    ] {
+        for _ in 0..25 {
+            map.translate_keyboard(direction.clone(), &mut rect);
+            assert_eq!(rect.extent(), extent);
+        }
+    }
+    map.center_to(&CoordinatePoint { x: 100, y: -100 }, &mut rect);
+    assert_eq!(rect.extent(), extent);
+    for _ in 0..10 {
+        map.zoom([1, 1], &mut rect);
+        assert!(rect.extent().0 >= 1 && rect.extent().1 >= 1);
+    }
+}
+
+#[test]
+fn pan_keeps_cell_widths_test() {
+    let data = Data {
+        width: 12,
+        height: 1,
+        data: "abcdefghijkl".chars().collect(),
+        first_point_coordinate: CoordinatePoint { x: 0, y: 0 },
+        overlay: Overlay::example(&CoordinatePoint { x: 0, y: 0 }),
+    };
+    let mut map = ShowMultiMap::with_settings(
+        vec![DataWithMetadata { key: 0, data }],
+        ShowMultiMapSettings {
+            colorbar: None,
+            boundary_factor_min: 100,
+            ..test_settings()
+        },
+    );
+    let mut state = map.default_state();
+    // 3 points on 10 pixels, so the data points are 3 or 4 pixels wide
+    state.shown_rectangle = Some(ShowRect {
+        left_top: ShowPoint { x: 0, y: 0 },
+        right_bottom: ShowPoint { x: 3, y: 1 },
+    });
+    let mut widths = Vec::new();
+    for _ in 0..3 {
+        let rendered = map.render(10, 1, &mut state).unwrap();
+        widths.push(rendered.iter().filter(|c| **c == 'c').count());
+        let rect = state.shown_rectangle.as_mut().unwrap();
+        map.translate(CoordinatePoint { x: 1, y: 0 }, rect);
+    }
+    assert!(widths[0] == 3 || widths[0] == 4);
+    assert!(widths.iter().all(|width| *width == widths[0]));
+}
+
+#[test]
+fn cell_range_test() {
+    for (pixels, points) in [(10, 3), (7, 7), (100, 13), (5, 1), (64, 9), (3, 8)] {
+        for first in -7..7 {
+            let widths = (0..points)
+                .map(|p| {
+                    let (start, end) = cell_range(p, pixels, points, first);
+                    end - start
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(widths.iter().sum::<usize>(), pixels);
+            let min = widths.iter().min().unwrap();
+            let max = widths.iter().max().unwrap();
+            assert!(max - min <= 1);
+            // the width of a data point does not depend on the first shown point
+            for (p, width) in widths.iter().enumerate() {
+                let (start, end) = cell_range(p + 1, pixels, points, first - 1);
+                assert_eq!(end - start, *width);
+            }
+            for pixel in 0..pixels {
+                let (point, _) = pixel_to_point(pixel, pixels, points, first, 0);
+                let (start, end) = cell_range(point, pixels, points, first);
+                assert!(start <= pixel && pixel < end);
+                let (start, end) = pixel_to_points(pixel, pixels, points, first);
+                assert!(start < end && end <= points);
+            }
+        }
+    }
+}
+
+#[test]
+fn render_average_test() {
+    let data = Data {
+        width: 4,
+        height: 1,
+        data: vec!['a', 'b', 'c', 'd'],
+        first_point_coordinate: CoordinatePoint { x: 0, y: 0 },
+        overlay: Overlay::example(&CoordinatePoint { x: 0, y: 0 }),
+    };
+    let map = ShowMultiMap::with_settings(
+        vec![DataWithMetadata { key: 0, data }],
+        ShowMultiMapSettings {
+            colorbar: None,
+            downsampling: Downsampling::Average,
+            ..test_settings()
+        },
+    );
+    assert_eq!(pixel_to_points(0, 2, 4, 0), (0, 2));
+    assert_eq!(pixel_to_points(1, 2, 4, 0), (2, 4));
+    let mut state = map.default_state();
+    let rendered = map.render(2, 1, &mut state).unwrap();
+    // the char-implementation of average picks the first data point
+    assert_eq!(rendered, vec!['a', 'c']);
+}
+
+#[test]
+fn select_limit_test() {
+    let map = ShowMultiMap::<usize, char>::with_settings(Vec::new(), test_settings());
+    let point = |x| CoordinatePoint { x, y: 0 };
+    let selected = [point(0), point(1)].into_iter().collect();
+    // ctrl-click adds or removes a point
+    let added = map.select(&point(2), true, &selected, None).unwrap();
+    assert_eq!(added.len(), 3);
+    let removed = map.select(&point(1), true, &selected, Some(2)).unwrap();
+    assert_eq!(removed, [point(0)].into_iter().collect());
+    // a plain click selects only the clicked point, or nothing if it was selected
+    let single = map.select(&point(2), false, &selected, Some(1)).unwrap();
+    assert_eq!(single, [point(2)].into_iter().collect());
+    assert!(map
+        .select(&point(1), false, &selected, None)
+        .unwrap()
+        .is_empty());
+    // rejected clicks do not change anything
+    assert!(map.select(&point(2), true, &selected, Some(2)).is_none());
+    assert!(map.select(&point(2), false, &selected, Some(0)).is_none());
+}
+
+#[test]
+fn selection_undo_redo_test() {
+    let mut state = MultimapState::<usize>::default();
+    let point = |x| CoordinatePoint { x, y: 0 };
+    assert!(!state.undo_selection());
+    for x in 0..3 {
+        state.push_selection_history();
+        state.selected.insert(point(x));
+    }
+    assert!(state.undo_selection());
+    assert_eq!(state.selected.len(), 2);
+    assert!(state.undo_selection());
+    assert!(state.redo_selection());
+    assert_eq!(state.selected.len(), 2);
+    // a new change discards the redo history
+    state.push_selection_history();
+    state.selected.clear();
+    assert!(!state.redo_selection());
+    assert!(state.undo_selection());
+    assert_eq!(state.selected.len(), 2);
+    for _ in 0..2 * SELECTION_HISTORY_DEPTH {
+        state.push_selection_history();
+        state.selected.insert(point(state.selected.len() as i32));
+    }
+    assert_eq!(state.selection_undo.len(), SELECTION_HISTORY_DEPTH);
+}
+
+#[test]
+fn truncate_with_ellipsis_test() {
+    let font = FontOptions {
+        font: crate::Font::Font8x8,
+        background_is_transparent: true,
+        background_opacity: 0.,
+        font_height: 1.,
+        rtl: false,
+        horizontal_scale: 1.,
+    };
+    let full = font.render("Title...").unwrap();
+    let truncated = truncate_with_ellipsis("Title", &font, full.width as usize);
+    assert!(truncated == font.render_opt("Titl..."));
+    assert!(truncate_with_ellipsis("Title", &font, 1).is_none());
+}
+
+#[test]
+fn colorbar_labels_test() {
+    assert_eq!(
+        ColorbarLabels::Auto.candidates(-1234.5),
+        vec!["-1.2345E+03", "-1.234E+03", "-1.23E+03", "-1.2E+03"]
+    );
+    assert_eq!(
+        ColorbarLabels::Scientific(2).candidates(0.0123),
+        vec!["+1.2E-02"]
+    );
+    assert_eq!(ColorbarLabels::Decimal(1).candidates(2.25), vec!["2.2"]);
+    // e.g. timestamps in milliseconds are not representable as f32
+    assert_eq!(
+        ColorbarLabels::Decimal(0).candidates(1_700_000_000_123.),
+        vec!["1700000000123"]
+    );
+}
+
+#[test]
+fn subplot_rect_test() {
+    let data = |key| DataWithMetadata {
+        key,
+        data: Data {
+            width: 2,
+            height: 2,
+            data: vec!['a'; 4],
+            first_point_coordinate: CoordinatePoint { x: 0, y: 0 },
+            overlay: Overlay::example(&CoordinatePoint { x: 0, y: 0 }),
+        },
+    };
+    let map = ShowMultiMap::with_settings(
+        vec![data(0), data(1), data(2)],
+        ShowMultiMapSettings {
+            colorbar: None,
+            ..test_settings()
+        },
+    );
+    let mut state = map.default_state();
+    // 2x2 subplots of size 9x4, separated by boundaries of thickness 2
+    assert_eq!(map.subplot_rect(&0, [20, 10], &state), Some([0, 0, 9, 4]));
+    assert_eq!(map.subplot_rect(&1, [20, 10], &state), Some([11, 0, 9, 4]));
+    assert_eq!(map.subplot_rect(&2, [20, 10], &state), Some([0, 6, 9, 4]));
+    assert_eq!(map.subplot_rect(&3, [20, 10], &state), None);
+    state.to_plot.insert(0, false);
+    assert_eq!(map.subplot_rect(&0, [20, 10], &state), None);
+    assert_eq!(map.subplot_rect(&2, [20, 10], &state), Some([11, 0, 9, 10]));
+}
+
+#[test]
+fn height_for_width_test() {
+    let data = |key, first_point_coordinate| DataWithMetadata {
+        key,
+        data: Data {
+            width: 4,
+            height: 2,
+            data: vec!['a'; 8],
+            first_point_coordinate,
+            overlay: Overlay::example(&CoordinatePoint { x: 0, y: 0 }),
+        },
+    };
+    let map = ShowMultiMap::with_settings(
+        vec![
+            data(0, CoordinatePoint { x: 0, y: 0 }),
+            data(1, CoordinatePoint { x: 0, y: 1 }),
+        ],
+        test_settings(),
+    );
+    let mut state = map.default_state();
+    // data extent: 4x3; two subplots side by side: (34 - 4 - 2 - 2) / 2 = 13 pixels wide
+    assert_eq!(map.height_for_width(34, &state), Some(9));
+    state.to_plot.insert(0, false);
+    // data extent: 4x2; single plot 28 pixels wide
+    assert_eq!(map.height_for_width(34, &state), Some(14));
+    state.to_plot.insert(1, false);
+    assert_eq!(map.height_for_width(34, &state), None);
+}
+
+#[test]
+fn relative_colorbar_thickness_test() {
+    let data = Data {
+        width: 2,
+        height: 2,
+        data: vec!['a', 'b', 'c', 'd'],
+        first_point_coordinate: CoordinatePoint { x: 0, y: 0 },
+        overlay: Overlay::example(&CoordinatePoint { x: 0, y: 0 }),
+    };
+    let map = ShowMultiMap::with_settings(
+        vec![DataWithMetadata { key: 0, data }],
+        ShowMultiMapSettings {
+            colorbar_relative_thickness: Some(RelativeThickness {
+                fraction: 0.25,
+                min: 3,
+                max: 10,
+            }),
+            ..test_settings()
+        },
+    );
+    let mut state = map.default_state();
+    // 25% of 20 pixels, plus boundary of thickness 2
+    assert_eq!(map.subplot_rect(&0, [20, 4], &state), Some([0, 0, 13, 4]));
+    let rendered = map.render(20, 4, &mut state).unwrap();
+    assert_eq!(&rendered[13..15], &['-', '-']);
+    assert_eq!(&rendered[15..20], &['c'; 5]);
+    let hover = |x| map.convert_multimap2bitmap(MultiMapPoint { x, y: 0 }, [20, 4], &state);
+    assert!(matches!(hover(14), crate::MultiMapPosition::NotHovering));
+    assert!(matches!(hover(15), crate::MultiMapPosition::Colorbar(_)));
+    // clamped to min and max
+    assert_eq!(map.subplot_rect(&0, [8, 4], &state), Some([0, 0, 3, 4]));
+    assert_eq!(map.subplot_rect(&0, [100, 4], &state), Some([0, 0, 88, 4]));
+}
+
+#[test]
+fn label_background_opacity_test() {
+    let font = FontOptions {
+        font: crate::Font::Font8x8,
+        background_is_transparent: true,
+        background_opacity: 0.5,
+        font_height: 1.,
+        rtl: false,
+        horizontal_scale: 1.,
+    };
+    let label = font.render("-").unwrap();
+    let width = label.width as usize;
+    let mut data = vec![egui::Color32::WHITE; width * 8];
+    draw_axis_label(
+        &mut data,
+        &label,
+        0,
+        0,
+        width,
+        &font,
+        &egui::Color32::BLACK,
+        &TextCompositing::Additive,
+    );
+    // the first row of '-' is empty, so the white data is darkened
+    assert!(data[..width].iter().all(|c| c.r() < 200 && c.a() == 255));
+    // glyph pixels are still bright
+    assert!(data.iter().any(|c| c == &egui::Color32::WHITE));
+}
+
+#[test]
+fn text_compositing_test() {
+    let font = FontOptions {
+        font: crate::Font::Font8x8,
+        background_is_transparent: true,
+        background_opacity: 0.,
+        font_height: 1.,
+        rtl: false,
+        horizontal_scale: 1.,
+    };
+    let label = font.render("-").unwrap();
+    let width = label.width as usize;
+    let draw = |font: &FontOptions, compositing| {
+        let mut data = vec![egui::Color32::WHITE; width * 8];
+        draw_axis_label(
+            &mut data,
+            &label,
+            0,
+            0,
+            width,
+            font,
+            &egui::Color32::BLACK,
+            &compositing,
+        );
+        data
+    };
+    // on white data, additive texts vanish
+    let additive = draw(&font, TextCompositing::Additive);
+    assert!(additive.iter().all(|c| c == &egui::Color32::WHITE));
+    let over = draw(&font, TextCompositing::Over(egui::Color32::BLACK));
+    assert!(over.iter().any(|c| c == &egui::Color32::BLACK));
+    assert_eq!(over[0], egui::Color32::WHITE);
+    // the text color is used on a solid background, too
+    let solid = FontOptions {
+        background_is_transparent: false,
+        ..font
+    };
+    let over = draw(&solid, TextCompositing::Over(egui::Color32::RED));
+    assert!(over.iter().any(|c| c == &egui::Color32::RED));
+    assert_eq!(over[0], egui::Color32::BLACK);
+    assert!(!over.contains(&egui::Color32::WHITE));
+}
+
+#[test]
+fn overlay_with_bitmaps_test() {
+    let dot = || BitMapText {
+        data: vec![255; 4],
+        width: 2,
+        height: 2,
+    };
+    let bitmaps = (0..3)
+        .map(|x| (CoordinatePoint { x, y: 0 }, dot()))
+        .collect();
+    let overlay = Overlay::with_bitmaps(FontOptions::default(), false, bitmaps, "");
+    assert_eq!(overlay.overlay_indices.len(), 3);
+    // identical bitmaps are stored once
+    assert_eq!(overlay.overlay_bitmaps.len(), 1);
+    assert!(overlay.overlay_bitmaps[0] == dot());
+}
+
+#[test]
+fn colorbar_gap_test() {
+    let data = Data {
+        width: 1,
+        height: 1,
+        data: vec!['x'],
+        first_point_coordinate: CoordinatePoint { x: 0, y: 0 },
+        overlay: Overlay::example(&CoordinatePoint { x: 0, y: 0 }),
+    };
+    let map = ShowMultiMap::with_settings(
+        vec![DataWithMetadata { key: 0, data }],
+        ShowMultiMapSettings {
+            colorbar_gap: Some(1),
+            boundary_unselected: ColorWithThickness {
+                color: 'r',
+                thickness: 0,
+            },
+            ..test_settings()
+        },
+    );
+    let mut state = map.default_state();
+    let rendered = map.render(8, 1, &mut state).unwrap();
+    assert_eq!(rendered.iter().collect::<String>(), "xxx-aaaa");
+}
+
+#[test]
+fn data_bounds_test() {
+    let data = |key, x, y| DataWithMetadata {
+        key,
+        data: Data {
+            width: 2,
+            height: 3,
+            data: vec!['a'; 6],
+            first_point_coordinate: CoordinatePoint { x, y },
+            overlay: Overlay::example(&CoordinatePoint { x, y }),
+        },
+    };
+    let map = ShowMultiMap::with_settings(vec![data(0, -1, 0), data(1, 5, 2)], test_settings());
+    let mut state = map.default_state();
+    assert_eq!(
+        map.data_bounds(&state),
+        Some(CoordinateRect {
+            left_top: CoordinatePoint { x: -1, y: 0 },
+            right_bottom: CoordinatePoint { x: 7, y: 5 },
+        })
+    );
+    state.to_plot.insert(0, false);
+    assert_eq!(
+        map.data_bounds(&state),
+        Some(CoordinateRect {
+            left_top: CoordinatePoint { x: 5, y: 2 },
+            right_bottom: CoordinatePoint { x: 7, y: 5 },
+        })
+    );
+    state.to_plot.insert(1, false);
+    assert_eq!(map.data_bounds(&state), None);
+}
+
+#[test]
+fn render_empty_test() {
+    let map = ShowMultiMap::<usize, char>::with_settings(Vec::new(), test_settings());
+    let mut state = map.default_state();
+    assert!(matches!(
+        map.render(4, 3, &mut state),
+        Err(RenderProblem::NoData)
+    ));
+    // the text does not fit, so only the background is shown
+    assert_eq!(map.render_empty(4, 3, "No data"), vec!['.'; 12]);
+}
+
+#[test]
+fn zoom_per_axis_test() {
+    let mut map = ShowMultiMap::<usize, char>::with_settings(Vec::new(), test_settings());
+    let mut rect = ShowRect {
+        left_top: ShowPoint { x: 0, y: 0 },
+        right_bottom: ShowPoint { x: 20, y: 10 },
+    };
+    map.zoom([2, 0], &mut rect);
+    assert_eq!(rect.extent(), (16, 10));
+    map.zoom([0, -1], &mut rect);
+    assert_eq!(rect.extent(), (16, 12));
+    map.zoom([1, 1], &mut rect);
+    assert_eq!(rect.extent(), (14, 10));
+    assert!(rect.left_top == ShowPoint { x: 3, y: 0 });
+}
+
+#[test]
+fn zoom_extent_limits_test() {
+    let mut map = ShowMultiMap::<usize, char>::with_settings(
+        Vec::new(),
+        ShowMultiMapSettings {
+            min_shown_extent: Some(8),
+            max_shown_extent: Some(24),
+            ..test_settings()
+        },
+    );
+    let mut rect = ShowRect {
+        left_top: ShowPoint { x: 0, y: 0 },
+        right_bottom: ShowPoint { x: 20, y: 10 },
+    };
+    // y stops at 8, x continues
+    map.zoom([2, 2], &mut rect);
+    assert_eq!(rect.extent(), (16, 8));
+    map.zoom([2, 2], &mut rect);
+    assert_eq!(rect.extent(), (12, 8));
+    // x stops at 24, y continues
+    map.zoom([-10, -4], &mut rect);
+    assert_eq!(rect.extent(), (24, 16));
+    map.zoom([-1, -1], &mut rect);
+    assert_eq!(rect.extent(), (24, 18));
+    // already too large: zooming out is ignored, zooming in works
+    let mut rect = ShowRect {
+        left_top: ShowPoint { x: 0, y: 0 },
+        right_bottom: ShowPoint { x: 30, y: 30 },
+    };
+    map.zoom([-1, 1], &mut rect);
+    assert_eq!(rect.extent(), (30, 28));
+}
+
+#[test]
+fn pan_bounds_test() {
+    let data = Data {
+        width: 10,
+        height: 10,
+        data: vec!['x'; 100],
+        first_point_coordinate: CoordinatePoint { x: 0, y: 0 },
+        overlay: Overlay::example(&CoordinatePoint { x: 0, y: 0 }),
+    };
+    let mut map = ShowMultiMap::with_settings(
+        vec![DataWithMetadata { key: 0, data }],
+        ShowMultiMapSettings {
+            pan_bounds: Some(PanBounds {
+                visible_fraction: 0.5,
+            }),
+            ..test_settings()
+        },
+    );
+    let mut rect = ShowRect {
+        left_top: ShowPoint { x: 3, y: 3 },
+        right_bottom: ShowPoint { x: 7, y: 7 },
+    };
+    // half of the 4x4 rectangle stays on the data
+    map.translate(CoordinatePoint { x: 100, y: -100 }, &mut rect);
+    assert!(rect.left_top == ShowPoint { x: 8, y: -2 });
+    assert_eq!(rect.extent(), (4, 4));
+    map.translate_keyboard(KeyBoardDirection::Right, &mut rect);
+    assert!(rect.left_top == ShowPoint { x: 8, y: -2 });
+    map.translate_keyboard(KeyBoardDirection::Down, &mut rect);
+    assert!(rect.left_top == ShowPoint { x: 8, y: -1 });
+    // larger than the data: half of the data stays visible
+    let mut rect = ShowRect {
+        left_top: ShowPoint { x: 0, y: 0 },
+        right_bottom: ShowPoint { x: 20, y: 20 },
+    };
+    map.translate(CoordinatePoint { x: -100, y: 0 }, &mut rect);
+    assert!(rect.left_top == ShowPoint { x: -15, y: 0 });
+    map.pan_bounds = None;
+    map.translate(CoordinatePoint { x: -100, y: 0 }, &mut rect);
+    assert!(rect.left_top == ShowPoint { x: -115, y: 0 });
+}
+
+#[test]
+fn overlay_builder_test() {
+    let font = FontOptions {
+        font: crate::Font::Font8x8,
+        ..Default::default()
+    };
+    let point = |x| CoordinatePoint { x, y: 0 };
+    let builder = Overlay::builder(font)
+        .text(point(0), "a")
+        .text(point(1), "a")
+        .text(point(2), "b")
+        // Font8x8 cannot render an empty string
+        .text(point(3), "");
+    assert_eq!(builder.failed(), &[point(3)]);
+    let builder = builder.text(point(3), "c").show_coordinates(false);
+    assert!(builder.failed().is_empty());
+    let overlay = builder.build().unwrap();
+    assert_eq!(overlay.overlay_indices.len(), 4);
+    assert_eq!(overlay.overlay_bitmaps.len(), 3);
+    assert!(overlay.coordinate_corners.is_empty());
+    assert!(overlay.title.is_empty());
+}
+
+#[test]
+fn nice_length_test() {
+    assert_eq!(nice_length(0.), None);
+    assert_eq!(nice_length(f64::NAN), None);
+    assert_eq!(nice_length(1.), Some((1., 0)));
+    assert_eq!(nice_length(3.7), Some((2., 0)));
+    assert_eq!(nice_length(730.), Some((500., 0)));
+    let (length, decimals) = nice_length(0.013).unwrap();
+    assert!((length - 0.01).abs() < 1e-12);
+    assert_eq!(decimals, 2);
+}
+
+#[test]
+fn render_scale_bar_test() {
+    let data = Data {
+        width: 10,
+        height: 10,
+        data: vec!['x'; 100],
+        first_point_coordinate: CoordinatePoint { x: 0, y: 0 },
+        overlay: Overlay::example(&CoordinatePoint { x: 0, y: 0 }),
+    };
+    let map = ShowMultiMap::with_settings(
+        vec![DataWithMetadata { key: 0, data }],
+        ShowMultiMapSettings {
+            colorbar: None,
+            scale_bar: Some(ScaleBar {
+                position: Corner::LeftBottom,
+                color: 'S',
+                thickness: 2,
+                units_per_point: 1.,
+                unit: String::new(),
+            }),
+            ..test_settings()
+        },
+    );
+    let mut state = map.default_state();
+    let (width, height) = (100, 100);
+    let rendered = map.render(width, height, &mut state).unwrap();
+    // 100 pixels for 10 points: the bar shows 2 points, which are 20 pixels
+    let bar_rows = rendered
+        .chunks(width)
+        .filter(|row| row.iter().filter(|&&c| c == 'S').count() == 20)
+        .count();
+    assert_eq!(bar_rows, 2);
+}
+
+#[test]
+fn grid_lines_test() {
+    let grid = GridLines {
+        spacing: 5,
+        color: 'M',
+        thickness: 1,
+        minor_divisions: 4,
+        minor_color: 'm',
+        minor_thickness: 1,
+    };
+    // 10 pixels per point, starting at coordinate -2: major lines at 0 and 5, minor lines in between
+    let hits = (0..100)
+        .map(|pixel| grid.hit(pixel, 100, 10, -2))
+        .collect::<Vec<_>>();
+    assert_eq!(hits[20], Some(true));
+    assert_eq!(hits[21], None);
+    assert_eq!(hits[30], Some(false));
+    assert_eq!(hits[70], Some(true));
+    assert_eq!(hits.iter().filter(|h| **h == Some(true)).count(), 2);
+    assert_eq!(hits.iter().filter(|h| **h == Some(false)).count(), 8);
+    // minor lines are skipped if they are too dense
+    assert!((0..100)
+        .map(|pixel| grid.hit(pixel, 100, 100, 0))
+        .all(|h| h != Some(false)));
+}
+
+#[test]
+fn accumulate_scroll_test() {
+    let mut state = MultimapState::<()>::default();
+    assert_eq!(state.accumulate_scroll(1.), 1);
+    assert_eq!(state.accumulate_scroll(0.4), 0);
+    assert_eq!(state.accumulate_scroll(0.4), 0);
+    assert_eq!(state.accumulate_scroll(0.4), 1);
+    assert_eq!(state.accumulate_scroll(-0.3), 0);
+    assert_eq!(state.accumulate_scroll(-0.4), 0);
+    assert_eq!(state.accumulate_scroll(-0.4), 0);
+    assert_eq!(state.accumulate_scroll(-0.4), -1);
+    // a step is only taken once the total crosses a whole step
+    state.reset_scroll();
+    assert_eq!(state.accumulate_scroll(0.6), 0);
+    assert_eq!(state.accumulate_scroll(0.6), 1);
+    assert_eq!(state.accumulate_scroll(-1.6), -1);
+    // the remainder is dropped when the gesture ends
+    state.reset_scroll();
+    assert_eq!(state.accumulate_scroll(0.6), 0);
+    // panning is accumulated separately, switching drops the other remainder
+    assert_eq!(state.accumulate_pan(0.6), 0);
+    assert_eq!(state.accumulate_scroll(0.6), 0);
+    assert_eq!(state.accumulate_pan(-0.6), 0);
+    assert_eq!(state.accumulate_pan(-0.6), -1);
+}
+
+#[test]
+fn colorbar_hover_test() {
+    let data = Data {
+        width: 2,
+        height: 2,
+        data: vec!['a', 'b', 'c', 'd'],
+        first_point_coordinate: CoordinatePoint { x: 0, y: 0 },
+        overlay: Overlay::example(&CoordinatePoint { x: 0, y: 0 }),
+    };
+    // colorbar: 4 columns, boundary: 2 columns
+    let map = ShowMultiMap::with_settings(vec![DataWithMetadata { key: 0, data }], test_settings());
+    let state = map.default_state();
+    let size = [16, 6];
+    let hover = |x, y| map.convert_multimap2bitmap(MultiMapPoint { x, y }, size, &state);
+    // boundary columns
+    for x in 10..12 {
+        assert!(matches!(hover(x, 0), crate::MultiMapPosition::NotHovering));
+    }
+    let value = |x, y| match hover(x, y) {
+        crate::MultiMapPosition::Colorbar(f) => f,
+        _ => panic!("not on colorbar at {x}|{y}"),
+    };
+    assert_eq!(value(12, 0), 1.);
+    assert_eq!(value(15, 1), 1.);
+    assert_eq!(value(12, 2), 0.5);
+    assert_eq!(value(12, 3), 0.5);
+    assert_eq!(value(12, 4), 0.);
+    assert_eq!(value(15, 5), 0.);
+}
+
+#[test]
+fn colorbar_placement_test() {
+    let data = Data {
+        width: 2,
+        height: 2,
+        data: vec!['a', 'b', 'c', 'd'],
+        first_point_coordinate: CoordinatePoint { x: 0, y: 0 },
+        overlay: Overlay::example(&CoordinatePoint { x: 0, y: 0 }),
+    };
+    let mut map =
+        ShowMultiMap::with_settings(vec![DataWithMetadata { key: 0, data }], test_settings());
+    let mut state = map.default_state();
+    assert_eq!(map.height_for_width(16, &state), Some(10));
+    let pixel = |position, x, y| matches!(position, crate::MultiMapPosition::Pixel(0, p) if p == CoordinatePoint { x, y });
+
+    // colorbar: 4 rows at the bottom, boundary: 2 rows
+    map.colorbar_placement = ColorbarPlacement::Bottom;
+    assert_eq!(map.height_for_width(16, &state), Some(22));
+    let size = [6, 16];
+    let rendered = map.render(size[0], size[1], &mut state).unwrap();
+    assert_eq!(&rendered[10 * 6..11 * 6], &['-'; 6]);
+    assert_eq!(&rendered[14 * 6..15 * 6], &['a', 'a', 'b', 'b', 'c', 'c']);
+    let hover = |x, y| map.convert_multimap2bitmap(MultiMapPoint { x, y }, size, &state);
+    assert!(pixel(hover(0, 0), 0, 0));
+    assert!(pixel(hover(5, 9), 1, 1));
+    assert!(matches!(hover(0, 10), crate::MultiMapPosition::NotHovering));
+    assert!(matches!(hover(0, 12), crate::MultiMapPosition::Colorbar(f) if f == 0.));
+    assert!(matches!(hover(2, 13), crate::MultiMapPosition::Colorbar(f) if f == 0.5));
+    assert!(matches!(hover(5, 15), crate::MultiMapPosition::Colorbar(f) if f == 1.));
+    assert!(map.render(6, 5, &mut state).is_err());
+
+    // colorbar: 4 columns at the left, boundary: 2 columns
+    map.colorbar_placement = ColorbarPlacement::Left;
+    let size = [16, 6];
+    let rendered = map.render(size[0], size[1], &mut state).unwrap();
+    assert_eq!(&rendered[..6], &['c', 'c', 'c', 'c', '-', '-']);
+    let hover = |x, y| map.convert_multimap2bitmap(MultiMapPoint { x, y }, size, &state);
+    assert!(matches!(hover(0, 0), crate::MultiMapPosition::Colorbar(f) if f == 1.));
+    assert!(matches!(hover(3, 5), crate::MultiMapPosition::Colorbar(f) if f == 0.));
+    assert!(matches!(hover(5, 0), crate::MultiMapPosition::NotHovering));
+    assert!(pixel(hover(6, 0), 0, 0));
+    assert!(pixel(hover(15, 5), 1, 1));
+    assert_eq!(map.subplot_rect(&0, size, &state), Some([6, 0, 10, 6]));
+}
+
+#[test]
+fn drag_outline_test() {
+    let data = Data {
+        width: 4,
+        height: 4,
+        data: vec!['x'; 16],
+        first_point_coordinate: CoordinatePoint { x: 0, y: 0 },
+        overlay: Overlay::example(&CoordinatePoint { x: 0, y: 0 }),
+    };
+    let mut map = ShowMultiMap::with_settings(
+        vec![DataWithMetadata { key: 0, data }],
+        ShowMultiMapSettings {
+            colorbar: None,
+            drag_outline: Some(ColorWithThickness {
+                color: 'o',
+                thickness: 1,
+            }),
+            ..test_settings()
+        },
+    );
+    map.drag_start(&CoordinatePoint { x: 1, y: 1 });
+    map.drag_is_ongoing(&CoordinatePoint { x: 2, y: 2 });
+    let mut state = map.default_state();
+    let rendered = map.render(8, 8, &mut state).unwrap();
+    let rows = rendered
+        .chunks(8)
+        .map(|row| row.iter().collect::<String>())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        rows,
+        vec![
+            "xxxxxxxx", "xxxxxxxx", "xxooooxx", "xxoxxoxx", "xxoxxoxx", "xxooooxx", "xxxxxxxx",
+            "xxxxxxxx",
+        ]
+    );
+}
+
+#[test]
+fn coordinate_corners_test() {
+    let render = |corners: &[Corner]| {
+        let data = Data {
+            width: 40,
+            height: 40,
+            data: vec!['x'; 1600],
+            first_point_coordinate: CoordinatePoint { x: 0, y: 0 },
+            overlay: Overlay::builder(FontOptions {
+                font: crate::Font::Font8x8,
+                ..Default::default()
+            })
+            .coordinate_corners(corners)
+            .build()
+            .unwrap(),
+        };
+        let map = ShowMultiMap::with_settings(
+            vec![DataWithMetadata { key: 0, data }],
+            ShowMultiMapSettings {
+                colorbar: None,
+                ..test_settings()
+            },
+        );
+        let mut state = map.default_state();
+        map.render(80, 80, &mut state).unwrap()
+    };
+    let quadrant = |rendered: &[char], right: bool, bottom: bool| {
+        rendered
+            .chunks(80)
+            .skip(if bottom { 40 } else { 0 })
+            .take(40)
+            .flat_map(|row| row[if right { 40 } else { 0 }..][..40].to_vec())
+            .collect::<String>()
+    };
+    let none = render(&[]);
+    let all = render(&Corner::ALL);
+    let left_top = render(&[Corner::LeftTop]);
+    for (right, bottom) in [(false, false), (true, false), (false, true), (true, true)] {
+        assert_ne!(
+            quadrant(&all, right, bottom),
+            quadrant(&none, right, bottom)
+        );
+        let expected = if (right, bottom) == (false, false) {
+            &all
+        } else {
+            &none
+        };
+        assert_eq!(
+            quadrant(&left_top, right, bottom),
+            quadrant(expected, right, bottom)
+        );
+    }
+}
+
+#[test]
+fn colorbar_position_test() {
+    // colorbar: 4 columns, limits (0., 1.)
+    let mut map = ShowMultiMap::<usize, char>::with_settings(Vec::new(), test_settings());
+    assert_eq!(
+        map.colorbar_position(1., [16, 6]),
+        Some([[12., 0.], [16., 0.]])
+    );
+    assert_eq!(
+        map.colorbar_position(0.5, [16, 6]),
+        Some([[12., 3.], [16., 3.]])
+    );
+    assert_eq!(
+        map.colorbar_position(-2., [16, 6]),
+        Some([[12., 6.], [16., 6.]])
+    );
+    assert_eq!(map.colorbar_position(f64::NAN, [16, 6]), None);
+    map.colorbar_placement = ColorbarPlacement::Bottom;
+    assert_eq!(
+        map.colorbar_position(0.25, [16, 6]),
+        Some([[4., 2.], [4., 6.]])
+    );
+    map.set_colorbar_visible(false);
+    assert_eq!(map.colorbar_position(0.5, [16, 6]), None);
+}
+
+#[test]
+fn colorbar_scale_test() {
+    let labels = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let formatted = labels.clone();
+    let data = Data {
+        width: 4,
+        height: 4,
+        data: vec!['x'; 16],
+        first_point_coordinate: CoordinatePoint { x: 0, y: 0 },
+        overlay: Overlay::example(&CoordinatePoint { x: 0, y: 0 }),
+    };
+    // colorbar: 4 columns, limits (1., 100.) in data units
+    let mut map = ShowMultiMap::with_settings(
+        vec![DataWithMetadata { key: 0, data }],
+        ShowMultiMapSettings {
+            colorbar_formatter: Some(Box::new(move |value| {
+                formatted.lock().unwrap().push(value);
+                format!("{value}")
+            })),
+            colorbar_ticks: 3,
+            colorbar_scale: crate::ScaleMode::Log10,
+            ..test_settings()
+        },
+    );
+    // the lower limit has no logarithm
+    assert!(!map.set_colorbar_limits(0., 100.));
+    assert!(map.set_colorbar_limits(1., 100.));
+    // the marker is placed on the logarithmic scale
+    assert_eq!(
+        map.colorbar_position(10., [16, 6]),
+        Some([[12., 3.], [16., 3.]])
+    );
+    assert_eq!(
+        map.colorbar_position(100., [16, 6]),
+        Some([[12., 0.], [16., 0.]])
+    );
+    assert_eq!(map.colorbar_position(-1., [16, 6]), None);
+    // hovered values are in data units
+    let mut state = map.default_state();
+    let value = |y| match map.convert_multimap2bitmap(MultiMapPoint { x: 12, y }, [16, 6], &state) {
+        crate::MultiMapPosition::Colorbar(f) => f,
+        _ => panic!("not on colorbar at 12|{y}"),
+    };
+    assert!((value(0) - 100.).abs() < 1e-9);
+    assert!((value(2) - 10.).abs() < 1e-9);
+    assert!((value(4) - 1.).abs() < 1e-9);
+    // the labels are in data units, too
+    map.render(200, 200, &mut state).unwrap();
+    // each label is formatted once per tried font height
+    let mut labels = labels.lock().unwrap().clone();
+    labels.dedup();
+    assert_eq!(labels.len(), 3);
+    for (label, expected) in labels.iter().zip([100., 10., 1.]) {
+        assert!((label - expected).abs() < 1e-9, "{label} != {expected}");
+    }
+}
+
+#[test]
+fn selection_style_test() {
+    let render = |selection_style| {
+        let data = Data {
+            width: 4,
+            height: 1,
+            data: vec!['x'; 4],
+            first_point_coordinate: CoordinatePoint { x: 0, y: 0 },
+            overlay: Overlay::example(&CoordinatePoint { x: 0, y: 0 }),
+        };
+        let map = ShowMultiMap::with_settings(
+            vec![DataWithMetadata { key: 0, data }],
+            ShowMultiMapSettings {
+                colorbar: None,
+                selection_style,
+                ..test_settings()
+            },
+        );
+        let mut state = map.default_state();
+        state.selected.insert(CoordinatePoint { x: 1, y: 0 });
+        map.render(4, 1, &mut state).unwrap()
+    };
+    // one pixel per data point: too small for boundaries
+    assert_eq!(render(SelectionStyle::Outline), vec!['x'; 4]);
+    assert_eq!(render(SelectionStyle::Fill(0.5)), vec!['x', 'w', 'x', 'x']);
+    let blended = egui::Color32::RED.blend(egui::Color32::BLUE, 0.5);
+    assert!(blended.r() > 0 && blended.b() > 0 && blended.g() == 0);
+}
+
+#[test]
+fn snap_to_data_test() {
+    let data = Data {
+        width: 10,
+        height: 10,
+        data: vec!['x'; 100],
+        first_point_coordinate: CoordinatePoint { x: 0, y: 0 },
+        overlay: Overlay::example(&CoordinatePoint { x: 0, y: 0 }),
+    };
+    let mut map =
+        ShowMultiMap::with_settings(vec![DataWithMetadata { key: 0, data }], test_settings());
+    let mut state = map.default_state();
+    let rect = |ltx, lty, rbx, rby| ShowRect {
+        left_top: ShowPoint { x: ltx, y: lty },
+        right_bottom: ShowPoint { x: rbx, y: rby },
+    };
+    // double click on the left top corner cell, while zoomed in
+    let mut shown = rect(3, 3, 7, 7);
+    map.center_to(&CoordinatePoint { x: 0, y: 0 }, &mut shown);
+    state.shown_rectangle = Some(shown);
+    map.snap_to_data(&mut state);
+    assert!(state.shown_rectangle == Some(rect(0, 0, 4, 4)));
+    // double click on the right bottom corner cell
+    let mut shown = rect(3, 3, 7, 7);
+    map.center_to(&CoordinatePoint { x: 9, y: 9 }, &mut shown);
+    state.shown_rectangle = Some(shown);
+    map.snap_to_data(&mut state);
+    assert!(state.shown_rectangle == Some(rect(6, 6, 10, 10)));
+    // zoomed out: the data stays fully visible
+    state.shown_rectangle = Some(rect(-25, -5, 5, 25));
+    map.snap_to_data(&mut state);
+    assert!(state.shown_rectangle == Some(rect(-20, -5, 10, 25)));
+}
+
+#[test]
+fn data_iter_test() {
+    let data = Data {
+        width: 3,
+        height: 2,
+        data: "abcdef".chars().collect(),
+        first_point_coordinate: CoordinatePoint { x: -1, y: 5 },
+        overlay: Overlay::example(&CoordinatePoint { x: 0, y: 0 }),
+    };
+    let point = |x, y| CoordinatePoint { x, y };
+    assert_eq!(
+        data.coordinates().collect::<Vec<_>>(),
+        vec![
+            point(-1, 5),
+            point(0, 5),
+            point(1, 5),
+            point(-1, 6),
+            point(0, 6),
+            point(1, 6)
+        ]
+    );
+    assert!(data.coordinates().all(|p| data.contains(&p)));
+    let (last, color) = data.iter().last().unwrap();
+    assert_eq!((last, *color), (point(1, 6), 'f'));
+    assert_eq!(data.iter().count(), 6);
+}
+
+#[test]
+fn colorbar_font_test() {
+    let render = |colorbar_font| {
+        let data = Data {
+            width: 4,
+            height: 4,
+            data: vec!['x'; 16],
+            first_point_coordinate: CoordinatePoint { x: 0, y: 0 },
+            overlay: Overlay::example(&CoordinatePoint { x: 0, y: 0 }),
+        };
+        let map = ShowMultiMap::with_settings(
+            vec![DataWithMetadata { key: 0, data }],
+            ShowMultiMapSettings {
+                colorbar: Some((crate::colors::Gradient(vec!['a', 'b']), 100, (0., 1.))),
+                colorbar_font,
+                ..test_settings()
+            },
+        );
+        let mut state = map.default_state();
+        map.render(200, 100, &mut state).unwrap()
+    };
+    let overlay_font = Overlay::example(&CoordinatePoint { x: 0, y: 0 }).font;
+    assert!(render(None) == render(Some(overlay_font)));
+    let opaque = FontOptions {
+        font: crate::Font::EguiMonospace,
+        font_height: 12.,
+        ..Default::default()
+    };
+    assert!(render(None) != render(Some(opaque)));
+}
+
+#[test]
+fn colorbar_ticks_test() {
+    let render = |colorbar_ticks| {
+        render_with_colorbar(ShowMultiMapSettings {
+            colorbar_ticks,
+            ..colorbar_test_settings()
+        })
+    };
+    // one label per tick, separated by rows without text
+    for ticks in [2, 5, 10] {
+        assert_eq!(colorbar_label_count(&render(ticks)), ticks);
+    }
+    // fewer than two ticks are clamped
+    assert!(render(0) == render(2));
+}
+
+#[test]
+fn colorbar_formatter_test() {
+    let render = |colorbar_labels, colorbar_formatter| {
+        render_with_colorbar(ShowMultiMapSettings {
+            colorbar_labels,
+            colorbar_formatter,
+            ..colorbar_test_settings()
+        })
+    };
+    let decimal: ColorbarFormatter = Box::new(|value| format!("{value:.1}"));
+    let plain = render(ColorbarLabels::Auto, Some(decimal));
+    assert!(plain == render(ColorbarLabels::Decimal(1), None));
+    // the formatted string is drawn, e.g. with its unit
+    let units: ColorbarFormatter = Box::new(|value| format!("{value:.1} mV"));
+    let with_units = render(ColorbarLabels::Auto, Some(units));
+    assert_eq!(colorbar_label_count(&plain), 5);
+    assert_eq!(colorbar_label_count(&with_units), 5);
+    assert!(colorbar_label_columns(&with_units) > colorbar_label_columns(&plain));
+    let empty: ColorbarFormatter = Box::new(|_| String::new());
+    assert_eq!(
+        colorbar_label_count(&render(ColorbarLabels::Auto, Some(empty))),
+        0
+    );
+}
+
+#[test]
+fn min_font_height_test() {
+    let render = |min_font_height| {
+        let data = Data {
+            width: 4,
+            height: 4,
+            data: vec!['x'; 16],
+            first_point_coordinate: CoordinatePoint { x: 0, y: 0 },
+            overlay: Overlay::example(&CoordinatePoint { x: 0, y: 0 }),
+        };
+        let map = ShowMultiMap::with_settings(
+            vec![DataWithMetadata { key: 0, data }],
+            ShowMultiMapSettings {
+                colorbar: Some((crate::colors::Gradient(vec!['a', 'b']), 100, (0., 1.))),
+                colorbar_font: Some(FontOptions {
+                    font: crate::Font::EguiMonospace,
+                    font_height: 12.,
+                    ..Default::default()
+                }),
+                min_font_height,
+                ..test_settings()
+            },
+        );
+        let mut state = map.default_state();
+        map.render(200, 100, &mut state).unwrap()
+    };
+    // the labels fit at 11 pixels, which is only tried below a limit of 11
+    let without_labels = render(11.);
+    assert!(without_labels
+        .chunks(200)
+        .all(|row| row[100..].iter().all(|c| *c == 'a' || *c == 'b')));
+    assert!(render(10.) != without_labels);
+    assert!(render(8.) != without_labels);
+    assert!(render(f32::NAN) == render(8.));
+}
+
+#[test]
+fn data_serde_test() {
+    fn assert_serde<T: serde::Serialize + serde::de::DeserializeOwned>() {}
+    assert_serde::<Data<egui::Color32>>();
+    assert_serde::<crate::MultiBitmapWidgetSettings>();
+
+    let first = CoordinatePoint { x: 3, y: -2 };
+    let data = Data::<egui::Color32>::example(4, 3, first.clone());
+    let mut overlay_bitmaps = std::collections::HashMap::new();
+    overlay_bitmaps.insert(
+        first.clone(),
+        BitMapText {
+            data: vec![255; 4],
+            width: 2,
+            height: 2,
+        },
+    );
+    let with_bitmaps = Overlay::with_bitmaps(data.overlay.font.clone(), false, overlay_bitmaps, "");
+    for overlay in [&data.overlay, &with_bitmaps] {
+        let repr = OverlayRepr::from(overlay);
+        assert!(repr.overlays.iter().all(|source| match source {
+            OverlaySource::Text(text) => text == "FP",
+            OverlaySource::Bitmap(bitmap) => bitmap.width == 2,
+        }));
+        let restored = Overlay::try_from(repr).unwrap();
+        assert_eq!(restored.title, overlay.title);
+        assert_eq!(restored.coordinate_corners, overlay.coordinate_corners);
+        assert_eq!(restored.overlay_strings, overlay.overlay_strings);
+        let restored = restored.get_overlays().collect::<Vec<_>>();
+        let original = overlay.get_overlays().collect::<Vec<_>>();
+        assert_eq!(restored.len(), 1);
+        assert!(restored == original);
+    }
+
+    let mut repr = OverlayRepr::from(&data.overlay);
+    repr.overlay_indices.push((first.clone(), 1));
+    assert!(Overlay::try_from(repr).is_err());
+
+    // round trip through an actual format, with rendered and given overlays
+    for data in [
+        data,
+        Data {
+            overlay: with_bitmaps,
+            ..Data::<egui::Color32>::example(4, 3, first)
+        },
+    ] {
+        let json = serde_json::to_string(&data).unwrap();
+        let restored: Data<egui::Color32> = serde_json::from_str(&json).unwrap();
+        assert_eq!((restored.width, restored.height), (data.width, data.height));
+        assert_eq!(restored.data, data.data);
+        assert_eq!(restored.first_point_coordinate, data.first_point_coordinate);
+        assert_eq!(restored.overlay.title, data.overlay.title);
+        let restored = restored.overlay.get_overlays().collect::<Vec<_>>();
+        let original = data.overlay.get_overlays().collect::<Vec<_>>();
+        assert!(!original.is_empty() && restored == original);
+    }
+}
+
+#[test]
+fn legend_test() {
+    let data = || Data {
+        width: 10,
+        height: 6,
+        data: vec!['x'; 60],
+        first_point_coordinate: CoordinatePoint { x: 0, y: 0 },
+        overlay: Overlay::builder(FontOptions::default())
+            .show_coordinates(false)
+            .build_lossy(),
+    };
+    let font = FontOptions {
+        font: crate::Font::EguiMonospace,
+        background_is_transparent: false,
+        font_height: 12.,
+        ..Default::default()
+    };
+    let render = |legend: Option<Legend>, [width, height]: [usize; 2]| {
+        let map = ShowMultiMap::with_settings(
+            vec![DataWithMetadata {
+                key: 0,
+                data: data(),
+            }],
+            ShowMultiMapSettings {
+                colorbar: None,
+                // no boundaries around the points, so that only the legend uses 'r'
+                boundary_unselected: ColorWithThickness {
+                    color: 'r',
+                    thickness: 0,
+                },
+                legend,
+                ..test_settings()
+            },
+        );
+        let mut state = map.default_state();
+        map.render(width, height, &mut state).unwrap()
+    };
+    let without = render(None, [600, 120]);
+    assert!(without.iter().all(|c| c == &'x'));
+    let legend = Legend::english(Corner::RightBottom, font.clone());
+    let with = render(Some(legend.clone()), [600, 120]);
+    let rows = with.chunks(600).collect::<Vec<_>>();
+    // the legend is in the right bottom corner
+    assert!(rows[..60].iter().all(|row| row.iter().all(|c| c == &'x')));
+    assert!(rows.iter().all(|row| row[..300].iter().all(|c| c == &'x')));
+    for swatch in ['w', 'r', '.', 'g', '-'] {
+        assert!(with.contains(&swatch));
+    }
+    // empty labels are skipped
+    let only_no_data = Legend {
+        text_selected: String::new(),
+        text_unselected: String::new(),
+        ..legend.clone()
+    };
+    let with = render(Some(only_no_data), [600, 120]);
+    assert!(with.contains(&'.') && !with.contains(&'w') && !with.contains(&'r'));
+    // nothing is drawn if the legend does not fit
+    assert!(render(Some(legend), [40, 24]).iter().all(|c| c == &'x'));
+}
+
+#[test]
+fn overlay_order_test() {
+    let overlay =
+        Data::<egui::Color32>::example_circle(4, 3, CoordinatePoint { x: 2, y: 1 }).overlay;
+    let points = overlay
+        .get_overlays()
+        .map(|(point, _)| point.clone())
+        .collect::<Vec<_>>();
+    let mut sorted = points.clone();
+    sorted.sort_by_key(|CoordinatePoint { x, y }| (*y, *x));
+    assert_eq!(points.len(), 12);
+    assert_eq!(points, sorted);
+}
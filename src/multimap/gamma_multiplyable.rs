@@ -18,6 +18,12 @@ pub trait BitMapDrawable {
     fn gray(gray: u8) -> Self;
     fn saturating_add(&self, gray: u8) -> Self;
     fn remove_alpha(self) -> Self;
+    /// Mix with another color, factor 0 keeps this color and factor 1 gives the other color
+    fn blend(self, other: Self, factor: f32) -> Self;
+    /// Combine several colors into one, used for downsampling. Returns None if there is no color
+    fn average(colors: impl Iterator<Item = Self>) -> Option<Self>
+    where
+        Self: Sized;
 }
 
 impl BitMapDrawable for char {
@@ -32,6 +38,18 @@ impl BitMapDrawable for char {
     fn remove_alpha(self) -> Self {
         self
     }
+
+    fn blend(self, other: Self, factor: f32) -> Self {
+        if factor > 0. {
+            other
+        } else {
+            self
+        }
+    }
+
+    fn average(mut colors: impl Iterator<Item = Self>) -> Option<Self> {
+        colors.next()
+    }
 }
 impl BitMapDrawable for egui::Color32 {
     fn gray(gray: u8) -> Self {
@@ -50,4 +68,27 @@ impl BitMapDrawable for egui::Color32 {
         let (r, g, b, _a) = self.to_tuple();
         Self::from_rgba_unmultiplied(r, g, b, 255)
     }
+    fn blend(self, other: Self, factor: f32) -> Self {
+        let factor = factor.clamp(0., 1.);
+        let a = self.gamma_multiply(1. - factor).to_array();
+        let b = other.gamma_multiply(factor).to_array();
+        let [r, g, b, a] = [0, 1, 2, 3].map(|i| a[i].saturating_add(b[i]));
+        Self::from_rgba_premultiplied(r, g, b, a)
+    }
+
+    fn average(colors: impl Iterator<Item = Self>) -> Option<Self> {
+        let mut sum = [0u32; 4];
+        let mut n = 0u32;
+        for c in colors {
+            for (s, c) in sum.iter_mut().zip(c.to_array()) {
+                *s += c as u32;
+            }
+            n += 1;
+        }
+        if n == 0 {
+            return None;
+        }
+        let [r, g, b, a] = sum.map(|s| ((s + n / 2) / n) as u8);
+        Some(Self::from_rgba_premultiplied(r, g, b, a))
+    }
 }